@@ -0,0 +1,215 @@
+//! Measures of a layout and of how well it was played
+//!
+//! An opening is a region of zeros joined as neighbors, a click in
+//! it reveals the whole region and the numbers around it. An island is a
+//! group of joined numbers away from any opening. 3BV, the minimum number
+//! of clicks to clear the layout, is one per opening and one per number
+//! away from the openings.
+
+use crate::{Action, Answer, Board, DisplayStatus, State, Topology};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub struct Metrics {
+    pub bbbv: usize,
+    pub openings: usize,
+    pub islands: usize,
+}
+
+/// Clicks played in a game, rejected ones included
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Clicks {
+    pub left: usize,
+    pub right: usize,
+    pub chord: usize,
+    /// Accepted clicks that changed the board
+    pub useful: usize,
+}
+
+/// How well a finished game was played, a lost game
+/// only counts the 3BV it solved
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
+pub struct Efficiency {
+    pub bbbv: usize,
+    pub solved: usize,
+    /// Milliseconds
+    pub time: u64,
+    pub clicks: Clicks,
+    /// Solved 3BV per second, 0 for a game without time
+    pub bbbv_per_second: f64,
+    /// Solved 3BV per click
+    pub ioe: f64,
+    /// Solved 3BV per useful click
+    pub throughput: f64,
+}
+
+impl Clicks {
+    pub fn total(&self) -> usize {
+        self.left + self.right + self.chord
+    }
+    pub(crate) fn add(&mut self, act: Action, useful: bool) {
+        match act {
+            Action::Click => self.left += 1,
+            Action::RightClick => self.right += 1,
+            Action::Chord => self.chord += 1,
+            Action::Hint => return,
+        }
+        if useful {
+            self.useful += 1;
+        }
+    }
+}
+
+/// The openings and the plaques they reveal
+struct Regions {
+    /// Opening of every zero, `None` elsewhere
+    opening: Vec<Vec<Option<usize>>>,
+    /// Whether a plaque is revealed by an opening
+    cleared: Vec<Vec<bool>>,
+    count: usize,
+}
+
+/// Every plaque marked false
+fn unseen(answer: &Answer) -> Vec<Vec<bool>> {
+    answer.board.iter().map(|row| vec![false; row.len()]).collect()
+}
+
+/// The region of the plaques `joined` to (x, y), marked as seen
+fn flood<F>(
+    topology: &dyn Topology,
+    x: usize,
+    y: usize,
+    seen: &mut [Vec<bool>],
+    joined: F,
+) -> Vec<(usize, usize)>
+where F: Fn(usize, usize) -> bool {
+    let mut region = vec![(x, y)];
+    seen[x][y] = true;
+    let mut i = 0;
+    while i < region.len() {
+        let (cx, cy) = region[i];
+        for (nx, ny) in topology.neighbors(cx, cy) {
+            if !seen[nx][ny] && joined(nx, ny) {
+                seen[nx][ny] = true;
+                region.push((nx, ny));
+            }
+        }
+        i += 1;
+    }
+    region
+}
+
+fn regions(answer: &Answer, topology: &dyn Topology) -> Regions {
+    let mut opening: Vec<Vec<Option<usize>>> = answer.board
+        .iter()
+        .map(|row| vec![None; row.len()])
+        .collect();
+    let mut cleared = unseen(answer);
+    let mut seen = unseen(answer);
+    let mut count = 0;
+    for (x, y) in topology.cells() {
+        if answer.board[x][y] != State::Empty(0) || seen[x][y] {
+            continue;
+        }
+        let zero = |nx: usize, ny: usize| answer.board[nx][ny] == State::Empty(0);
+        for (i, j) in flood(topology, x, y, &mut seen, zero) {
+            opening[i][j] = Some(count);
+            cleared[i][j] = true;
+            for (nx, ny) in topology.neighbors(i, j) {
+                cleared[nx][ny] = true;
+            }
+        }
+        count += 1;
+    }
+    Regions { opening, cleared, count }
+}
+
+/// Whether a plaque counts for 3BV on its own
+fn lone(answer: &Answer, regions: &Regions, x: usize, y: usize) -> bool {
+    answer.board[x][y] != State::Mine && !regions.cleared[x][y]
+}
+
+pub(crate) fn metrics(answer: &Answer, topology: &dyn Topology) -> Metrics {
+    let regions = regions(answer, topology);
+    let mut seen = unseen(answer);
+    let (mut lones, mut islands) = (0, 0);
+    for (x, y) in topology.cells() {
+        if !lone(answer, &regions, x, y) {
+            continue;
+        }
+        lones += 1;
+        if !seen[x][y] {
+            islands += 1;
+            flood(topology, x, y, &mut seen, |nx, ny| lone(answer, &regions, nx, ny));
+        }
+    }
+    Metrics { bbbv: regions.count + lones, openings: regions.count, islands }
+}
+
+/// 3BV of a layout already solved on a board: the openings
+/// with a revealed zero and the revealed numbers away from them
+pub(crate) fn solved_bbbv(answer: &Answer, board: &Board, topology: &dyn Topology) -> usize {
+    let regions = regions(answer, topology);
+    let mut opened = vec![false; regions.count];
+    let mut lones = 0;
+    for (x, y) in topology.cells() {
+        if !matches!(board.board[x][y], DisplayStatus::Visible(_)) {
+            continue;
+        }
+        if let Some(i) = regions.opening[x][y] {
+            opened[i] = true;
+        } else if lone(answer, &regions, x, y) {
+            lones += 1;
+        }
+    }
+    opened.iter().filter(|&&o| o).count() + lones
+}
+
+pub(crate) fn efficiency(bbbv: usize, solved: usize, time: u64, clicks: Clicks) -> Efficiency {
+    let ratio = |n: usize, d: f64| if d > 0.0 { n as f64 / d } else { 0.0 };
+    Efficiency {
+        bbbv,
+        solved,
+        time,
+        clicks,
+        bbbv_per_second: ratio(solved, time as f64 / 1000.0),
+        ioe: ratio(solved, clicks.total() as f64),
+        throughput: ratio(solved, clicks.useful as f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::{parse_answer, parse_view};
+
+    #[test]
+    fn metrics_test() {
+        // One opening on the left, three numbers off it on the right
+        let metrics_of = |text: &str| {
+            let (answer, config) = parse_answer(text).unwrap();
+            metrics(&answer, &*config.topology())
+        };
+        assert_eq!(metrics_of("....*\n...*.\n....*\n"), Metrics { bbbv: 4, openings: 1, islands: 1 });
+        assert_eq!(metrics_of("*.*\n"), Metrics { bbbv: 1, openings: 0, islands: 1 });
+        // Two openings apart
+        assert_eq!(metrics_of("..*..\n..*..\n"), Metrics { bbbv: 2, openings: 2, islands: 0 });
+    }
+    #[test]
+    fn solved_bbbv_test() {
+        let (answer, config) = parse_answer("..*..\n..*..\n*....\n").unwrap();
+        let topology = config.topology();
+        assert_eq!(metrics(&answer, &*topology).bbbv, 4);
+        let (board, _) = parse_view("02###\n13###\n#####\n").unwrap();
+        assert_eq!(solved_bbbv(&answer, &board, &*topology), 1);
+        let (board, _) = parse_view("02#2#\n13###\n##1##\n").unwrap();
+        assert_eq!(solved_bbbv(&answer, &board, &*topology), 2);
+    }
+    #[test]
+    fn efficiency_test() {
+        let clicks = Clicks { left: 3, right: 1, chord: 0, useful: 2 };
+        let e = efficiency(8, 4, 2000, clicks);
+        assert_eq!((e.bbbv_per_second, e.ioe, e.throughput), (2.0, 1.0, 2.0));
+        assert_eq!(efficiency(8, 4, 0, Clicks::default()).bbbv_per_second, 0.0);
+    }
+}
@@ -1,14 +1,30 @@
 use std::f32::consts::E;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::{
+    analysis::Efficiency,
+    solver::Hint,
+    stats::{BestTime, Level, Record, Stats, Streaks},
+    topology::{Grid, Hex},
+    FirstClick,
     GameConfig,
     Game,
     GameStatus,
     Input,
+    Action,
+    MinesweeperError,
     win,
     lose,
 };
 use tokio::sync::{Mutex, mpsc};
+use tokio::time::{self, Instant};
+
+/// How often the clock is sent to the frontend while it runs
+const TICK_PERIOD: Duration = Duration::from_millis(100);
+
+/// Name of the statistics store in the data directory
+const STATS_FILE: &str = "stats.json";
 
 pub struct AsyncProcInputTx {
     pub tx: Mutex<mpsc::Sender<AsyncInput>>,
@@ -17,6 +33,22 @@ pub enum AsyncInput {
     StartGame(GameConfig),
     LeftClick(usize, usize),
     RightClick(usize, usize),
+    Chord(usize, usize),
+    Probability,
+    Hint,
+    WinRate(Option<Level>),
+    BestTimes,
+    Streaks,
+    Undo,
+    Redo,
+    Pause,
+    Resume,
+    /// Move the viewport of a wrap-around board by rows and columns
+    Scroll(isize, isize),
+    /// Draw another layer of a cube board
+    Layer(usize),
+    SaveGame(PathBuf),
+    LoadGame(PathBuf),
     EndGame,
     EndSession,
     EndProgram,
@@ -24,40 +56,181 @@ pub enum AsyncInput {
 pub enum AsyncOutput {
     GameStatus(GameStatus),
     Display(Vec<String>),
-    Error(String),
+    Error(MinesweeperError),
     Window(usize, usize),
+    /// The shape of the board, sent with the window
+    Grid(Grid),
+    Seed(u64),
+    Probability(Vec<Vec<Option<f64>>>),
+    Hint(Hint),
+    Efficiency(Efficiency),
+    WinRate(Option<f64>),
+    BestTimes(Vec<BestTime>),
+    Streaks(Streaks),
+    /// Milliseconds on the game clock
+    Tick(u64),
+    /// Mines minus flags, negative with too many flags
+    MinesRemaining(isize),
+    /// The board of a lost game with every mine, the one hit and the wrong flags
+    FinalBoard(Vec<String>),
+    /// The layer drawn of a cube board, sent with every display
+    Layer(usize),
 }
 pub async fn main_thread(
     mut input: mpsc::Receiver<AsyncInput>,
     output: mpsc::Sender<AsyncOutput>,
+    data_dir: Option<PathBuf>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let mut game = Game::placehold();
-    while let Some(instruction) = input.recv().await {
+    let mut next_tick = Instant::now() + TICK_PERIOD;
+    loop {
+        let instruction = match time::timeout_at(next_tick, input.recv()).await {
+            Ok(Some(instruction)) => instruction,
+            Ok(None) => break,
+            Err(_) => {
+                // No input before the tick, send the clock if it runs
+                if game.timer.running() && !game.paused() {
+                    output.send(AsyncOutput::Tick(game.elapsed())).await?;
+                }
+                next_tick = Instant::now() + TICK_PERIOD;
+                continue;
+            },
+        };
         match instruction {
             AsyncInput::StartGame(game_config) => {
                 // this arem is for the game to start run
                 let start_receit = Game::init_ref(game_config, &mut game);
                 if start_receit.is_err() {
                     output
-                        .send(AsyncOutput::Error(start_receit.unwrap_err().to_string()))
+                        .send(AsyncOutput::Error(start_receit.unwrap_err()))
                         .await?;
                 }
                 else {
-                    // Send a game status update to the frontend
-                    output
-                        .send(AsyncOutput::GameStatus(GameStatus::InProgress))
-                        .await?;
-                    // Send a display update to the frontend
-                    // output.send(AsyncOutput::Display(game.display())).await;
                     // Send a Window to initialize the window and display the game
                     output.send(AsyncOutput::Window(game_config.width, game_config.height)).await?;
+                    output.send(AsyncOutput::Grid(game_config.grid)).await?;
+                    // Send the seed so that the board can be shared and replayed
+                    output.send(AsyncOutput::Seed(game.seed())).await?;
+                    // The hidden board, the game status and the mines left
+                    send_board(&game, &output).await?;
                 }
 
             },
             AsyncInput::LeftClick(x, y) => {
-
+                // The frontend clicks on the board drawn from the viewport
+                let (x, y) = game.from_viewport(x, y);
+                let click = Input { x, y, act: Action::Click };
+                send_update(&mut game, &click, &output, data_dir.as_deref()).await?;
             },
             AsyncInput::RightClick(x, y) => {
+                let (x, y) = game.from_viewport(x, y);
+                let click = Input { x, y, act: Action::RightClick };
+                send_update(&mut game, &click, &output, data_dir.as_deref()).await?;
+            },
+            AsyncInput::Chord(x, y) => {
+                let (x, y) = game.from_viewport(x, y);
+                let click = Input { x, y, act: Action::Chord };
+                send_update(&mut game, &click, &output, data_dir.as_deref()).await?;
+            },
+            // The hidden board of a paused game gives nothing away
+            AsyncInput::Probability | AsyncInput::Hint if game.paused() => {
+                output.send(AsyncOutput::Error(MinesweeperError::Paused)).await?;
+            },
+            AsyncInput::Probability => {
+                let probabilities = game.drawn(&game.probabilities());
+                output
+                    .send(AsyncOutput::Probability(probabilities))
+                    .await?;
+            },
+            AsyncInput::Hint => {
+                // Through `try_update`, so the hint is in the replay
+                let hinted = game
+                    .try_update(&Input { x: 0, y: 0, act: Action::Hint })
+                    .and_then(|_| {
+                        game.last_hint().cloned().ok_or(MinesweeperError::NoCertainMove)
+                    });
+                match hinted {
+                    Ok(mut hint) => {
+                        // Draw the layer of the hint first
+                        let layer = game.layer_of(hint.x);
+                        if layer != game.layer() && game.set_layer(layer).is_ok() {
+                            send_board(&game, &output).await?;
+                        }
+                        (hint.x, hint.y) = game.to_viewport(hint.x, hint.y);
+                        output.send(AsyncOutput::Hint(hint)).await?
+                    },
+                    Err(e) => output.send(AsyncOutput::Error(e)).await?,
+                }
+            },
+            AsyncInput::WinRate(level) => {
+                match load_stats(data_dir.as_deref()) {
+                    Ok(stats) => output.send(AsyncOutput::WinRate(stats.win_rate(level))).await?,
+                    Err(e) => output.send(AsyncOutput::Error(e)).await?,
+                }
+            },
+            AsyncInput::BestTimes => {
+                match load_stats(data_dir.as_deref()) {
+                    Ok(stats) => output.send(AsyncOutput::BestTimes(stats.best_times())).await?,
+                    Err(e) => output.send(AsyncOutput::Error(e)).await?,
+                }
+            },
+            AsyncInput::Streaks => {
+                match load_stats(data_dir.as_deref()) {
+                    Ok(stats) => output.send(AsyncOutput::Streaks(stats.streaks())).await?,
+                    Err(e) => output.send(AsyncOutput::Error(e)).await?,
+                }
+            },
+            AsyncInput::SaveGame(path) => {
+                if let Err(e) = game.save(&path) {
+                    output.send(AsyncOutput::Error(e)).await?;
+                }
+            },
+            AsyncInput::LoadGame(path) => {
+                match Game::load(&path) {
+                    Ok(loaded) => {
+                        game = loaded;
+                        output.send(AsyncOutput::GameStatus(game.status)).await?;
+                        output
+                            .send(AsyncOutput::Window(game.config.width, game.config.height))
+                            .await?;
+                        output.send(AsyncOutput::Grid(game.config.grid)).await?;
+                        output.send(AsyncOutput::Seed(game.seed())).await?;
+                        send_board(&game, &output).await?;
+                    },
+                    Err(e) => output.send(AsyncOutput::Error(e)).await?,
+                }
+            },
+            AsyncInput::Undo => {
+                match game.undo() {
+                    Ok(()) => send_board(&game, &output).await?,
+                    Err(e) => output.send(AsyncOutput::Error(e)).await?,
+                }
+            },
+            AsyncInput::Redo => {
+                match game.redo() {
+                    Ok(()) => send_board(&game, &output).await?,
+                    Err(e) => output.send(AsyncOutput::Error(e)).await?,
+                }
+            },
+            AsyncInput::Pause => {
+                game.pause();
+                send_board(&game, &output).await?;
+            },
+            AsyncInput::Resume => {
+                game.resume();
+                send_board(&game, &output).await?;
+            },
+            AsyncInput::Layer(layer) => {
+                match game.set_layer(layer) {
+                    Ok(()) => send_board(&game, &output).await?,
+                    Err(e) => output.send(AsyncOutput::Error(e)).await?,
+                }
+            },
+            AsyncInput::Scroll(rows, columns) => {
+                match game.scroll(rows, columns) {
+                    Ok(()) => send_board(&game, &output).await?,
+                    Err(e) => output.send(AsyncOutput::Error(e)).await?,
+                }
             },
             _ => (),
         }
@@ -65,25 +238,386 @@ pub async fn main_thread(
     Ok(())
 }
 
+/// Apply an input to the game and report the result to the frontend
+/// A rejected input only sends an error, the board is left untouched.
+/// When the input ends the game the efficiency, and after a loss the
+/// final board, is sent, the replay and the statistics are written once
+async fn send_update(
+    game: &mut Game,
+    input: &Input,
+    output: &mpsc::Sender<AsyncOutput>,
+    data_dir: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let before = game.status;
+    if let Err(e) = game.try_update(input) {
+        output.send(AsyncOutput::Error(e)).await?;
+        return Ok(());
+    }
+    send_board(game, output).await?;
+    if game.status == before {
+        return Ok(());
+    }
+    if let Some(board) = game.loss_view() {
+        output.send(AsyncOutput::FinalBoard(board)).await?;
+    }
+    if let Some(efficiency) = game.efficiency() {
+        output.send(AsyncOutput::Efficiency(efficiency)).await?;
+        if let Some(dir) = data_dir {
+            if let Err(e) = game_over(game, dir) {
+                output.send(AsyncOutput::Error(e)).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write the replay of a finished game as `replays/<seed>-<time>.json`
+/// in the data directory and add it to the statistics,
+/// practice games are not counted
+fn game_over(game: &Game, data_dir: &Path) -> Result<(), MinesweeperError> {
+    let dir = data_dir.join("replays");
+    std::fs::create_dir_all(&dir)?;
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    game.replay().save(&dir.join(format!("{}-{}.json", game.seed(), time)))?;
+    match Record::new(game) {
+        Some(record) if !game.config.practice =>
+            Stats::record(&data_dir.join(STATS_FILE), record),
+        _ => Ok(()),
+    }
+}
 
+/// The statistics store in the data directory
+fn load_stats(data_dir: Option<&Path>) -> Result<Stats, MinesweeperError> {
+    let dir = data_dir.ok_or_else(|| MinesweeperError::Io {
+        message: "no data directory".to_string(),
+    })?;
+    Stats::load(&dir.join(STATS_FILE))
+}
+
+/// Send the board, the layer drawn, the game status,
+/// the mines left and the clock to the frontend
+async fn send_board(
+    game: &Game,
+    output: &mpsc::Sender<AsyncOutput>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut buffer: Vec<String> = vec![];
+    game.show_str(&mut buffer);
+    output.send(AsyncOutput::Display(buffer)).await?;
+    output.send(AsyncOutput::Layer(game.layer())).await?;
+    output.send(AsyncOutput::GameStatus(game.status)).await?;
+    output.send(AsyncOutput::MinesRemaining(game.mines_remaining())).await?;
+    output.send(AsyncOutput::Tick(game.elapsed())).await?;
+    Ok(())
+}
 
 /// Start a new game
 /// Write into the api_tx channel
 #[tauri::command(rename_all = "snake_case")]
+#[allow(clippy::too_many_arguments)] // every option is a field of the invoke call
 pub async fn start_game(
     width: String,
     height: String,
     mines: String,
+    seed: Option<String>,
+    no_guess: Option<bool>,
+    practice: Option<bool>,
+    questions: Option<bool>,
+    grid: Option<Grid>,
+    depth: Option<String>,
     input: tauri::State<'_, AsyncProcInputTx>,
-) -> Result<(), String> {
+) -> Result<(), MinesweeperError> {
     let input_tx = input.tx.lock().await;
-    let args = vec!["".to_string(), width, height, mines]; // Here the first argument is the program name
-    let game_config: GameConfig = GameConfig::init(&args)?;
+    let mut args = vec!["".to_string(), width, height, mines]; // Here the first argument is the program name
+    if let Some(seed) = seed.filter(|s| !s.is_empty()) {
+        args.push(seed);
+    }
+    let mut game_config: GameConfig = GameConfig::init(&args)?;
+    if no_guess.unwrap_or(false) {
+        // A no-guess board is expected to start with an opening
+        game_config = game_config
+            .with_no_guess(true)
+            .with_first_click(FirstClick::Opening);
+    }
+    game_config = game_config
+        .with_practice(practice.unwrap_or(false))
+        .with_questions(questions.unwrap_or(false))
+        .with_grid(grid.unwrap_or_default());
+    if let Some(depth) = depth.filter(|d| !d.is_empty()) {
+        let depth = depth
+            .parse::<usize>()
+            .map_err(|_| MinesweeperError::Parse { field: "depth" })?;
+        game_config = game_config.with_depth(depth);
+    }
     let start_game_config = AsyncInput::StartGame(game_config);
     input_tx
         .send(start_game_config)
         .await
-        .map_err(|e|e.to_string())
+        .map_err(|_| MinesweeperError::Disconnected)
+}
+
+/// Ask for the share of games won, on the level given or on all of them
+/// Write into the api_tx channel
+#[tauri::command(rename_all = "snake_case")]
+#[allow(clippy::too_many_arguments)] // every option is a field of the invoke call
+pub async fn win_rate(
+    width: Option<String>,
+    height: Option<String>,
+    mines: Option<String>,
+    grid: Option<Grid>,
+    depth: Option<String>,
+    no_guess: Option<bool>,
+    input: tauri::State<'_, AsyncProcInputTx>,
+) -> Result<(), MinesweeperError> {
+    let input_tx = input.tx.lock().await;
+    let level = match (width, height, mines) {
+        (Some(width), Some(height), Some(mines)) => Some(Level {
+            width: width.parse().map_err(|_| MinesweeperError::Parse { field: "width" })?,
+            height: height.parse().map_err(|_| MinesweeperError::Parse { field: "height" })?,
+            mines: mines.parse().map_err(|_| MinesweeperError::Parse { field: "mines" })?,
+            grid: grid.unwrap_or_default(),
+            depth: match depth.filter(|d| !d.is_empty()) {
+                Some(depth) => depth.parse().map_err(|_| MinesweeperError::Parse { field: "depth" })?,
+                None => 1,
+            },
+            no_guess: no_guess.unwrap_or(false),
+        }),
+        _ => None,
+    };
+    input_tx
+        .send(AsyncInput::WinRate(level))
+        .await
+        .map_err(|_| MinesweeperError::Disconnected)
+}
+
+/// Ask for the fastest win of every level played
+/// Write into the api_tx channel
+#[tauri::command(rename_all = "snake_case")]
+pub async fn best_times(
+    input: tauri::State<'_, AsyncProcInputTx>,
+) -> Result<(), MinesweeperError> {
+    let input_tx = input.tx.lock().await;
+    input_tx
+        .send(AsyncInput::BestTimes)
+        .await
+        .map_err(|_| MinesweeperError::Disconnected)
+}
+
+/// Ask for the current and the longest streaks
+/// Write into the api_tx channel
+#[tauri::command(rename_all = "snake_case")]
+pub async fn streaks(
+    input: tauri::State<'_, AsyncProcInputTx>,
+) -> Result<(), MinesweeperError> {
+    let input_tx = input.tx.lock().await;
+    input_tx
+        .send(AsyncInput::Streaks)
+        .await
+        .map_err(|_| MinesweeperError::Disconnected)
+}
+
+/// Left click on the plaque at (x, y)
+/// Write into the api_tx channel
+#[tauri::command(rename_all = "snake_case")]
+pub async fn left_click(
+    x: usize,
+    y: usize,
+    input: tauri::State<'_, AsyncProcInputTx>,
+) -> Result<(), MinesweeperError> {
+    let input_tx = input.tx.lock().await;
+    input_tx
+        .send(AsyncInput::LeftClick(x, y))
+        .await
+        .map_err(|_| MinesweeperError::Disconnected)
+}
+
+/// Right click on the plaque at (x, y)
+/// Write into the api_tx channel
+#[tauri::command(rename_all = "snake_case")]
+pub async fn right_click(
+    x: usize,
+    y: usize,
+    input: tauri::State<'_, AsyncProcInputTx>,
+) -> Result<(), MinesweeperError> {
+    let input_tx = input.tx.lock().await;
+    input_tx
+        .send(AsyncInput::RightClick(x, y))
+        .await
+        .map_err(|_| MinesweeperError::Disconnected)
+}
+
+/// Chord on the visible number at (x, y)
+/// Write into the api_tx channel
+#[tauri::command(rename_all = "snake_case")]
+pub async fn chord(
+    x: usize,
+    y: usize,
+    input: tauri::State<'_, AsyncProcInputTx>,
+) -> Result<(), MinesweeperError> {
+    let input_tx = input.tx.lock().await;
+    input_tx
+        .send(AsyncInput::Chord(x, y))
+        .await
+        .map_err(|_| MinesweeperError::Disconnected)
+}
+
+/// Play `action`, `l`, `r` or `c`, on the hexagon at axial (q, r)
+/// Write into the api_tx channel
+#[tauri::command(rename_all = "snake_case")]
+pub async fn axial_click(
+    q: isize,
+    r: usize,
+    action: String,
+    input: tauri::State<'_, AsyncProcInputTx>,
+) -> Result<(), MinesweeperError> {
+    let input_tx = input.tx.lock().await;
+    let (x, y) = Hex::from_axial(q, r).ok_or(MinesweeperError::Parse { field: "q" })?;
+    let click = match Action::from_letter(&action)? {
+        Action::RightClick => AsyncInput::RightClick(x, y),
+        Action::Chord => AsyncInput::Chord(x, y),
+        _ => AsyncInput::LeftClick(x, y),
+    };
+    input_tx
+        .send(click)
+        .await
+        .map_err(|_| MinesweeperError::Disconnected)
+}
+
+/// Ask for the mine probability of every plaque
+/// Write into the api_tx channel
+#[tauri::command(rename_all = "snake_case")]
+pub async fn probability(
+    input: tauri::State<'_, AsyncProcInputTx>,
+) -> Result<(), MinesweeperError> {
+    let input_tx = input.tx.lock().await;
+    input_tx
+        .send(AsyncInput::Probability)
+        .await
+        .map_err(|_| MinesweeperError::Disconnected)
+}
+
+/// Ask for one certain plaque and the reason
+/// Write into the api_tx channel
+#[tauri::command(rename_all = "snake_case")]
+pub async fn hint(
+    input: tauri::State<'_, AsyncProcInputTx>,
+) -> Result<(), MinesweeperError> {
+    let input_tx = input.tx.lock().await;
+    input_tx
+        .send(AsyncInput::Hint)
+        .await
+        .map_err(|_| MinesweeperError::Disconnected)
+}
+
+/// Take back the last move
+/// Write into the api_tx channel
+#[tauri::command(rename_all = "snake_case")]
+pub async fn undo(
+    input: tauri::State<'_, AsyncProcInputTx>,
+) -> Result<(), MinesweeperError> {
+    let input_tx = input.tx.lock().await;
+    input_tx
+        .send(AsyncInput::Undo)
+        .await
+        .map_err(|_| MinesweeperError::Disconnected)
+}
+
+/// Play the last move taken back again
+/// Write into the api_tx channel
+#[tauri::command(rename_all = "snake_case")]
+pub async fn redo(
+    input: tauri::State<'_, AsyncProcInputTx>,
+) -> Result<(), MinesweeperError> {
+    let input_tx = input.tx.lock().await;
+    input_tx
+        .send(AsyncInput::Redo)
+        .await
+        .map_err(|_| MinesweeperError::Disconnected)
+}
+
+/// Stop the clock and hide the board
+/// Write into the api_tx channel
+#[tauri::command(rename_all = "snake_case")]
+pub async fn pause(
+    input: tauri::State<'_, AsyncProcInputTx>,
+) -> Result<(), MinesweeperError> {
+    let input_tx = input.tx.lock().await;
+    input_tx
+        .send(AsyncInput::Pause)
+        .await
+        .map_err(|_| MinesweeperError::Disconnected)
+}
+
+/// Show the board and start the clock again
+/// Write into the api_tx channel
+#[tauri::command(rename_all = "snake_case")]
+pub async fn resume(
+    input: tauri::State<'_, AsyncProcInputTx>,
+) -> Result<(), MinesweeperError> {
+    let input_tx = input.tx.lock().await;
+    input_tx
+        .send(AsyncInput::Resume)
+        .await
+        .map_err(|_| MinesweeperError::Disconnected)
+}
+
+/// Scroll a wrap-around board by `rows` down and `columns` right,
+/// negative numbers scroll up and left
+/// Write into the api_tx channel
+#[tauri::command(rename_all = "snake_case")]
+pub async fn scroll(
+    rows: isize,
+    columns: isize,
+    input: tauri::State<'_, AsyncProcInputTx>,
+) -> Result<(), MinesweeperError> {
+    let input_tx = input.tx.lock().await;
+    input_tx
+        .send(AsyncInput::Scroll(rows, columns))
+        .await
+        .map_err(|_| MinesweeperError::Disconnected)
+}
+
+/// Draw the layer `layer` of a cube board, counted from 0
+/// Write into the api_tx channel
+#[tauri::command(rename_all = "snake_case")]
+pub async fn layer(
+    layer: usize,
+    input: tauri::State<'_, AsyncProcInputTx>,
+) -> Result<(), MinesweeperError> {
+    let input_tx = input.tx.lock().await;
+    input_tx
+        .send(AsyncInput::Layer(layer))
+        .await
+        .map_err(|_| MinesweeperError::Disconnected)
+}
+
+/// Save the game in progress to `path`
+/// Write into the api_tx channel
+#[tauri::command(rename_all = "snake_case")]
+pub async fn save_game(
+    path: String,
+    input: tauri::State<'_, AsyncProcInputTx>,
+) -> Result<(), MinesweeperError> {
+    let input_tx = input.tx.lock().await;
+    input_tx
+        .send(AsyncInput::SaveGame(PathBuf::from(path)))
+        .await
+        .map_err(|_| MinesweeperError::Disconnected)
+}
+
+/// Resume the game saved at `path`
+/// Write into the api_tx channel
+#[tauri::command(rename_all = "snake_case")]
+pub async fn load_game(
+    path: String,
+    input: tauri::State<'_, AsyncProcInputTx>,
+) -> Result<(), MinesweeperError> {
+    let input_tx = input.tx.lock().await;
+    input_tx
+        .send(AsyncInput::LoadGame(PathBuf::from(path)))
+        .await
+        .map_err(|_| MinesweeperError::Disconnected)
 }
 
 pub async fn output_handler<R: tauri::Runtime>(
@@ -94,18 +628,54 @@ pub async fn output_handler<R: tauri::Runtime>(
         AsyncOutput::Error(e) => {
             manager.emit_all("error", e).unwrap();
         }
+        AsyncOutput::Grid(grid) => {
+            manager.emit_all("grid", grid).unwrap();
+        }
         AsyncOutput::Window(w, h) => {
             create_window(w, h, manager).await;
         }
-        _ => (),
+        AsyncOutput::Display(board) => {
+            manager.emit_all("display", board).unwrap();
+        }
+        AsyncOutput::GameStatus(status) => {
+            manager.emit_all("game_status", status).unwrap();
+        }
+        AsyncOutput::Seed(seed) => {
+            // A u64 does not fit in a javascript number
+            manager.emit_all("seed", seed.to_string()).unwrap();
+        }
+        AsyncOutput::Probability(p) => {
+            manager.emit_all("probability", p).unwrap();
+        }
+        AsyncOutput::Hint(hint) => {
+            manager.emit_all("hint", hint).unwrap();
+        }
+        AsyncOutput::MinesRemaining(mines) => {
+            manager.emit_all("mines_remaining", mines).unwrap();
+        }
+        AsyncOutput::Layer(layer) => {
+            manager.emit_all("layer", layer).unwrap();
+        }
+        AsyncOutput::FinalBoard(board) => {
+            manager.emit_all("final_board", board).unwrap();
+        }
+        AsyncOutput::Tick(elapsed) => {
+            manager.emit_all("tick", elapsed).unwrap();
+        }
+        AsyncOutput::Efficiency(efficiency) => {
+            manager.emit_all("efficiency", efficiency).unwrap();
+        }
+        AsyncOutput::WinRate(rate) => {
+            manager.emit_all("win_rate", rate).unwrap();
+        }
+        AsyncOutput::BestTimes(times) => {
+            manager.emit_all("best_times", times).unwrap();
+        }
+        AsyncOutput::Streaks(streaks) => {
+            manager.emit_all("streaks", streaks).unwrap();
+        }
     };
 }
-// remember to call `.manage(MyState::default())`
-#[tauri::command]
-pub fn display(n: usize) -> Vec<String> {
-    vec![String::from("&#128681;"); n]
-}
-
 async fn create_window<R: tauri::Runtime>(
     w: usize, h: usize,
     manager: &impl tauri::Manager<R>
@@ -119,6 +689,6 @@ async fn create_window<R: tauri::Runtime>(
     manager
     .emit_all(
         "parameter_init",
-        format!("{{width: {}, height: {}}}", w, h)
+        serde_json::json!({ "width": w, "height": h }).to_string()
     ).unwrap();
 }
\ No newline at end of file
@@ -0,0 +1,153 @@
+use std::fmt;
+
+/// Everything that can go wrong in the engine
+///
+/// Serialized with a `kind` tag, e.g. `{"kind": "OutOfBounds", "x": 3, "y": 9}`,
+/// so that the frontend can react to each case.
+#[derive(Debug, PartialEq, Eq, Clone, serde::Serialize)]
+#[serde(tag = "kind")]
+pub enum MinesweeperError {
+    /// The configuration takes `width height mines [seed]`
+    ArgumentCount { found: usize },
+    /// A number could not be read, `field` tells which one
+    Parse { field: &'static str },
+    /// The terminal action is none of the known letters
+    InvalidAction { action: String },
+    /// More mines than plaques left free by the first click
+    TooManyMines { requested: usize, capacity: usize },
+    /// Too dense for a no-guess board to be found in time
+    TooDenseForNoGuess { requested: usize, capacity: usize },
+    /// No layout of the attempts could be cleared without guessing
+    NoGuessFailed { attempts: usize },
+    OutOfBounds { x: usize, y: usize },
+    AlreadyRevealed { x: usize, y: usize },
+    Flagged { x: usize, y: usize },
+    /// Chording needs a revealed number
+    NotRevealed { x: usize, y: usize },
+    /// Chording needs as many flags as the number
+    FlagMismatch { x: usize, y: usize, number: u8, flags: usize },
+    /// The solver finds nothing certain on the visible board
+    NoCertainMove,
+    NothingToUndo,
+    NothingToRedo,
+    /// No move is taken while the game is paused
+    Paused,
+    /// Only a board whose edges wrap around can be scrolled
+    NoWrap,
+    /// A layer that is not on the board, `layers` is 1 unless it is a cube
+    NoLayer { layers: usize },
+    /// Only a cube board has more than one layer
+    LayersWithoutCube { depth: usize },
+    /// A won or lost game can only be taken back in practice mode
+    UndoAfterEnd,
+    /// No move is taken once the game is won or lost
+    GameOver,
+    /// The game thread is not listening anymore
+    Disconnected,
+    /// Reading or writing a file failed
+    Io { message: String },
+    /// A save file that is not valid
+    SaveFormat { message: String },
+    /// A save file from another version of the format
+    SaveVersion { found: u32, expected: u32 },
+    /// A plain-text board that cannot be read, `line` counts from 1
+    Layout { line: usize, message: String },
+    /// A replay that does not end the way it was recorded
+    ReplayMismatch { expected: crate::GameStatus, found: crate::GameStatus },
+    /// A replay that does not take the time it was recorded with, in milliseconds
+    ReplayTime { expected: u64, found: u64 },
+    /// A video file of another program that cannot be read
+    Video { message: String },
+}
+
+impl fmt::Display for MinesweeperError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MinesweeperError::ArgumentCount { found } =>
+                write!(f, "Please input 3 or 4 arguments, found {}", found),
+            MinesweeperError::Parse { field } =>
+                write!(f, "Failed to parse {}", field),
+            MinesweeperError::InvalidAction { action } =>
+                write!(f, "Invalid action: {}", action),
+            MinesweeperError::TooManyMines { requested, capacity } =>
+                write!(f, "Too many mines: {} requested, at most {}", requested, capacity),
+            MinesweeperError::TooDenseForNoGuess { requested, capacity } =>
+                write!(f, "Too many mines for a no-guess board: {} requested, at most {}",
+                    requested, capacity),
+            MinesweeperError::NoGuessFailed { attempts } =>
+                write!(f, "Failed to generate a no-guess board in {} attempts", attempts),
+            MinesweeperError::OutOfBounds { x, y } =>
+                write!(f, "Out of bound: ({}, {})", x, y),
+            MinesweeperError::AlreadyRevealed { x, y } =>
+                write!(f, "Plaque ({}, {}) is already revealed", x, y),
+            MinesweeperError::Flagged { x, y } =>
+                write!(f, "Cannot click flagged plaque ({}, {})", x, y),
+            MinesweeperError::NotRevealed { x, y } =>
+                write!(f, "Cannot chord hidden plaque ({}, {})", x, y),
+            MinesweeperError::FlagMismatch { x, y, number, flags } =>
+                write!(f, "The {} at ({}, {}) has {} flags around it", number, x, y, flags),
+            MinesweeperError::NoCertainMove =>
+                write!(f, "No certain move, a guess is needed"),
+            MinesweeperError::NothingToUndo =>
+                write!(f, "Nothing to undo"),
+            MinesweeperError::NothingToRedo =>
+                write!(f, "Nothing to redo"),
+            MinesweeperError::Paused =>
+                write!(f, "The game is paused"),
+            MinesweeperError::NoWrap =>
+                write!(f, "Only a wrap-around board can be scrolled"),
+            MinesweeperError::NoLayer { layers } =>
+                write!(f, "The board only has {} layers", layers),
+            MinesweeperError::LayersWithoutCube { depth } =>
+                write!(f, "Only a cube board can have {} layers", depth),
+            MinesweeperError::UndoAfterEnd =>
+                write!(f, "Cannot undo a finished game outside practice mode"),
+            MinesweeperError::GameOver =>
+                write!(f, "The game is over"),
+            MinesweeperError::Disconnected =>
+                write!(f, "The game is not running"),
+            MinesweeperError::Io { message } =>
+                write!(f, "File error: {}", message),
+            MinesweeperError::SaveFormat { message } =>
+                write!(f, "Invalid save file: {}", message),
+            MinesweeperError::SaveVersion { found, expected } =>
+                write!(f, "Save file version {} is not supported, expected {}",
+                    found, expected),
+            MinesweeperError::Layout { line, message } =>
+                write!(f, "Invalid board at line {}: {}", line, message),
+            MinesweeperError::ReplayMismatch { expected, found } =>
+                write!(f, "The replay ends {:?} but was recorded {:?}", found, expected),
+            MinesweeperError::ReplayTime { expected, found } =>
+                write!(f, "The replay takes {} ms but was recorded in {} ms", found, expected),
+            MinesweeperError::Video { message } =>
+                write!(f, "Invalid video file: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for MinesweeperError {}
+
+impl From<std::io::Error> for MinesweeperError {
+    fn from(e: std::io::Error) -> MinesweeperError {
+        MinesweeperError::Io { message: e.to_string() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_serialize_test() {
+        let e = MinesweeperError::TooManyMines { requested: 101, capacity: 99 };
+        assert_eq!(
+            serde_json::to_string(&e).unwrap(),
+            r#"{"kind":"TooManyMines","requested":101,"capacity":99}"#
+        );
+        assert_eq!(e.to_string(), "Too many mines: 101 requested, at most 99");
+        assert_eq!(
+            serde_json::to_string(&MinesweeperError::NothingToUndo).unwrap(),
+            r#"{"kind":"NothingToUndo"}"#
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! Plain-text boards
+//!
+//! A layout tells where the mines are: `*` for a mine and `.` for a safe
+//! plaque. A view is what the player sees: `#` hidden, `F` flagged, `?`
+//! questioned and the digit of every revealed number, `a` to `q` for the
+//! numbers from 10 to 26 of the triangle and cube grids. Both may start
+//! with a header line `width height mines`, e.g.
+//!
+//! ```text
+//! 3 2 1
+//! ..*
+//! ...
+//! ```
+
+use crate::{
+    Answer,
+    Board,
+    DisplayStatus,
+    GameConfig,
+    MinesweeperError,
+    State,
+    count_mine,
+};
+
+/// Counts are written in one character, 10 and more as a letter
+const COUNT_RADIX: u32 = 36;
+
+/// Rows of a text board with their line number
+type Rows<'a> = Vec<(usize, &'a str)>;
+
+/// The header, if any, and the rows of a text board
+fn split(text: &str) -> Result<(Option<[usize; 3]>, Rows<'_>), MinesweeperError> {
+    let mut rows: Rows = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .collect();
+    let mut header = None;
+    if let Some(&(_, first)) = rows.first() {
+        let numbers: Vec<usize> = first
+            .split_whitespace()
+            .filter_map(|n| n.parse::<usize>().ok())
+            .collect();
+        if first.split_whitespace().count() == 3 && numbers.len() == 3 {
+            header = Some([numbers[0], numbers[1], numbers[2]]);
+            rows.remove(0);
+        }
+    }
+    let width = rows.first().map_or(0, |(_, row)| row.chars().count());
+    for &(line, row) in &rows {
+        if row.chars().count() != width {
+            return Err(MinesweeperError::Layout {
+                line,
+                message: format!("expected {} plaques, found {}", width, row.chars().count()),
+            });
+        }
+    }
+    if let Some([w, h, _]) = header {
+        if (w, h) != (width, rows.len()) {
+            return Err(MinesweeperError::Layout {
+                line: 1,
+                message: format!("header says {}x{}, the board is {}x{}", w, h, width, rows.len()),
+            });
+        }
+    }
+    Ok((header, rows))
+}
+
+/// Read a layout, the numbers are counted from the mines
+pub(crate) fn parse_answer(text: &str) -> Result<(Answer, GameConfig), MinesweeperError> {
+    let (header, rows) = split(text)?;
+    let mut board = vec![];
+    for (line, row) in rows {
+        let mut states = vec![];
+        for c in row.chars() {
+            states.push(match c {
+                '*' => State::Mine,
+                '.' => State::Empty(0),
+                _ => return Err(MinesweeperError::Layout {
+                    line,
+                    message: format!("unexpected '{}' in a layout", c),
+                }),
+            });
+        }
+        board.push(states);
+    }
+    let mines = board.iter().flatten().filter(|&&s| s == State::Mine).count();
+    if let Some([_, _, m]) = header {
+        if m != mines {
+            return Err(MinesweeperError::Layout {
+                line: 1,
+                message: format!("header says {} mines, the board has {}", m, mines),
+            });
+        }
+    }
+    let width = board.first().map_or(0, |row| row.len());
+    let config = GameConfig::new(width, board.len(), mines);
+    let mut answer = Answer { board };
+    count_mine(&mut answer, &*config.topology());
+    Ok((answer, config))
+}
+
+/// Read a view, with the number of mines from the header if there is one
+pub(crate) fn parse_view(text: &str) -> Result<(Board, Option<usize>), MinesweeperError> {
+    let (header, rows) = split(text)?;
+    let mut board = vec![];
+    for (line, row) in rows {
+        let mut plaques = vec![];
+        for c in row.chars() {
+            plaques.push(match c {
+                '#' => DisplayStatus::Hidden,
+                'F' => DisplayStatus::Flagged,
+                '?' => DisplayStatus::Questioned,
+                d if d.is_ascii_digit() || d.is_ascii_lowercase() =>
+                    DisplayStatus::Visible(d.to_digit(COUNT_RADIX).unwrap() as u8),
+                _ => return Err(MinesweeperError::Layout {
+                    line,
+                    message: format!("unexpected '{}' in a view", c),
+                }),
+            });
+        }
+        board.push(plaques);
+    }
+    let total_count = board
+        .iter()
+        .flatten()
+        .filter(|d| matches!(d, DisplayStatus::Visible(_)))
+        .count();
+    let mut board = Board { board, total_count, flags: 0 };
+    board.flags = board.count_flags();
+    Ok((board, header.map(|[_, _, m]| m)))
+}
+
+/// Write a layout with its header
+pub(crate) fn write_answer(answer: &Answer) -> String {
+    let width = answer.board.first().map_or(0, |row| row.len());
+    let mines = answer.board.iter().flatten().filter(|&&s| s == State::Mine).count();
+    let mut res = format!("{} {} {}\n", width, answer.board.len(), mines);
+    for row in &answer.board {
+        for s in row {
+            res.push(if *s == State::Mine { '*' } else { '.' });
+        }
+        res.push('\n');
+    }
+    res
+}
+
+/// Write a view with its header
+pub(crate) fn write_view(board: &Board, mines: usize) -> String {
+    let width = board.board.first().map_or(0, |row| row.len());
+    let mut res = format!("{} {} {}\n", width, board.board.len(), mines);
+    for row in &board.board {
+        for d in row {
+            match d {
+                DisplayStatus::Hidden => res.push('#'),
+                DisplayStatus::Flagged => res.push('F'),
+                DisplayStatus::Questioned => res.push('?'),
+                // Only on the board shown after a loss, written as played
+                DisplayStatus::Mine | DisplayStatus::Exploded => res.push('#'),
+                DisplayStatus::WrongFlag => res.push('F'),
+                DisplayStatus::Visible(c) =>
+                    res.push(char::from_digit(*c as u32, COUNT_RADIX).unwrap()),
+            }
+        }
+        res.push('\n');
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_answer_test() {
+        let (a, c) = parse_answer("*..\n.*.\n..*\n").unwrap();
+        assert_eq!((c.width, c.height, c.mines), (3, 3, 3));
+        assert_eq!(a.board, vec![
+            vec![State::Mine, State::Empty(2), State::Empty(1)],
+            vec![State::Empty(2), State::Mine, State::Empty(2)],
+            vec![State::Empty(1), State::Empty(2), State::Mine]
+        ]);
+        assert_eq!(write_answer(&a), "3 3 3\n*..\n.*.\n..*\n");
+        let (b, _) = parse_answer(&write_answer(&a)).unwrap();
+        assert_eq!(b.board, a.board);
+    }
+    #[test]
+    fn layout_view_test() {
+        let text = "3 2 1\n01F\n0#?\n";
+        let (b, mines) = parse_view(text).unwrap();
+        assert_eq!(mines, Some(1));
+        assert_eq!((b.total_count, b.flags), (3, 1));
+        assert_eq!(b.board[0][2], DisplayStatus::Flagged);
+        assert_eq!(b.board[1][1], DisplayStatus::Hidden);
+        assert_eq!(b.board[1][2], DisplayStatus::Questioned);
+        assert_eq!(write_view(&b, 1), text);
+        let (b, mines) = parse_view("12\n##").unwrap();
+        assert_eq!(mines, None);
+        assert_eq!(b.board[0][1], DisplayStatus::Visible(2));
+    }
+    #[test]
+    fn layout_view_count_test() {
+        // Every triangle touching the middle one by a corner is a mine
+        use crate::topology::{Grid, Neighborhood};
+        let (mut answer, config) = parse_answer("*****\n**.**\n*****\n").unwrap();
+        let config = config.with_grid(Grid::Triangle(Neighborhood::Vertex));
+        count_mine(&mut answer, &*config.topology());
+        let mut game = crate::Game::from_answer(answer, config);
+        game.try_update(&crate::Input { x: 1, y: 2, act: crate::Action::Click }).unwrap();
+        assert_eq!(game.board.board[1][2], DisplayStatus::Visible(12));
+        let text = write_view(&game.board, 14);
+        assert_eq!(text, "5 3 14\n#####\n##c##\n#####\n");
+        let (b, mines) = parse_view(&text).unwrap();
+        assert_eq!(mines, Some(14));
+        assert_eq!(b.board, game.board.board);
+    }
+    #[test]
+    fn layout_view_cube_test() {
+        // The middle of a 3x3x3 cube with all 26 neighbors mined
+        use crate::topology::Grid;
+        let layout = "***\n***\n***\n***\n*.*\n***\n***\n***\n***\n";
+        let (mut answer, _) = parse_answer(layout).unwrap();
+        let config = GameConfig::new(3, 3, 26).with_grid(Grid::Cube).with_depth(3);
+        count_mine(&mut answer, &*config.topology());
+        let mut game = crate::Game::from_answer(answer, config);
+        game.try_update(&crate::Input { x: 4, y: 1, act: crate::Action::Click }).unwrap();
+        assert_eq!(game.board.board[4][1], DisplayStatus::Visible(26));
+        let text = write_view(&game.board, 26);
+        assert!(text.contains("\n#q#\n"));
+        let (b, _) = parse_view(&text).unwrap();
+        assert_eq!(b.board, game.board.board);
+        // The terminal keeps every column three characters wide
+        game.set_layer(1).unwrap();
+        let mut shown = vec![];
+        game.show(&mut shown);
+        assert!(String::from_utf8(shown).unwrap().starts_with(" *  *  * \n * 26  * \n"));
+    }
+    #[test]
+    fn layout_error_test() {
+        assert_eq!(
+            parse_answer("..\n...\n").err(),
+            Some(MinesweeperError::Layout {
+                line: 2,
+                message: "expected 2 plaques, found 3".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_answer("2 1 2\n*.\n").err(),
+            Some(MinesweeperError::Layout {
+                line: 1,
+                message: "header says 2 mines, the board has 1".to_string(),
+            })
+        );
+        assert!(parse_answer("3 1 0\n..\n").is_err());
+        assert!(parse_answer("x.\n").is_err());
+        assert!(parse_view("*#\n").is_err());
+    }
+}
@@ -1,71 +1,302 @@
+pub mod analysis;
 pub mod cli;
+pub mod error;
+pub mod layout;
+pub mod replay;
+pub mod solver;
+pub mod stats;
+pub mod timer;
+pub mod topology;
+pub mod versioned;
+pub mod video;
 
-use rand::{seq::SliceRandom};
+pub use error::MinesweeperError;
+pub use topology::Topology;
+
+use rand::{seq::SliceRandom, SeedableRng};
+// The generator is named, `StdRng` may change between versions of rand
+use rand_chacha::ChaCha8Rng;
 use std::process;
 use std::process::Stdio;
 use std::io::{BufRead, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
 enum State {
     Empty(u8),
     Mine,
 }
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
 enum DisplayStatus {
     Hidden,
     Flagged,
+    /// Marked as unsure, played like a hidden plaque
+    Questioned,
     Visible(u8),
+    /// Only on the board shown after a loss, see `Game::loss_board`
+    Mine,
+    /// The mine that lost the game
+    Exploded,
+    /// A flag on a plaque without a mine
+    WrongFlag,
 }
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum GameStatus {
     InProgress,
     Won,
     Lost,
 }
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum Action {
     RightClick,
     Click,
+    /// Reveal the hidden neighbors of a visible number
+    /// whose flags are all placed
+    Chord,
+    /// Ask the solver for one certain plaque, the position is ignored
+    Hint,
+}
+
+impl Action {
+    /// The action of a terminal letter, `l`, `r` or `c`
+    pub fn from_letter(letter: &str) -> Result<Action, MinesweeperError> {
+        match letter {
+            "l" => Ok(Action::Click),
+            "r" => Ok(Action::RightClick),
+            "c" => Ok(Action::Chord),
+            a => Err(MinesweeperError::InvalidAction { action: a.to_string() }),
+        }
+    }
+}
+
+/// Which plaques are kept free of mines on the first click
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum FirstClick {
+    /// Only the clicked plaque is safe
+    #[default]
+    Safe,
+    /// The clicked plaque and all its neighbors are safe
+    Opening,
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Layouts tried before a no-guess board is given up
+const NO_GUESS_ATTEMPTS: usize = 500;
+/// Highest share of mines, in percent of the plaques left after the
+/// first click, for which a no-guess board is found within the attempts
+const NO_GUESS_DENSITY: usize = 22;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct GameConfig {
     width: usize,
     height: usize,
     mines: usize,
+    first_click: FirstClick,
+    seed: Option<u64>,
+    no_guess: bool,
+    practice: bool,
+    #[serde(default)]
+    questions: bool,
+    #[serde(default)]
+    grid: topology::Grid,
+    /// Layers of a cube board, 1 for the other grids
+    #[serde(default = "one_layer")]
+    depth: usize,
+}
+
+pub(crate) fn one_layer() -> usize {
+    1
 }
 
 impl GameConfig {
-    pub fn init(args: &Vec<String>) -> Result<GameConfig, &'static str> {
-        if args.len() != 4 {
-            return Err("Please input 3 arguments");
+    pub fn new(width: usize, height: usize, mines: usize) -> GameConfig {
+        GameConfig {
+            width,
+            height,
+            mines,
+            first_click: FirstClick::default(),
+            seed: None,
+            no_guess: false,
+            practice: false,
+            questions: false,
+            grid: topology::Grid::default(),
+            depth: 1,
+        }
+    }
+    pub fn with_first_click(mut self, first_click: FirstClick) -> GameConfig {
+        self.first_click = first_click;
+        self
+    }
+    /// Fix the seed of the board generator, a random one is picked otherwise
+    pub fn with_seed(mut self, seed: u64) -> GameConfig {
+        self.seed = Some(seed);
+        self
+    }
+    /// Only generate boards that can be cleared from the first click
+    /// without guessing
+    pub fn with_no_guess(mut self, no_guess: bool) -> GameConfig {
+        self.no_guess = no_guess;
+        self
+    }
+    /// Allow a loss to be undone
+    pub fn with_practice(mut self, practice: bool) -> GameConfig {
+        self.practice = practice;
+        self
+    }
+    /// Let the right click put a question mark after the flag
+    pub fn with_questions(mut self, questions: bool) -> GameConfig {
+        self.questions = questions;
+        self
+    }
+    /// Play on another shape than the rectangle
+    pub fn with_grid(mut self, grid: topology::Grid) -> GameConfig {
+        self.grid = grid;
+        self
+    }
+    /// Play on `depth` layers of the board, only a cube board
+    /// may have more than one, see `with_grid`
+    pub fn with_depth(mut self, depth: usize) -> GameConfig {
+        self.depth = depth;
+        self
+    }
+    /// Layers of the board, only a cube board has more than one
+    pub fn layers(&self) -> usize {
+        match self.grid {
+            topology::Grid::Cube => self.depth,
+            _ => 1,
+        }
+    }
+    /// The largest number of mines that still leaves room
+    /// for the safe zone of the first click, wherever it lands
+    fn capacity(&self, topology: &dyn Topology) -> usize {
+        let zone = match self.first_click {
+            FirstClick::Safe => 1,
+            FirstClick::Opening => 1 + topology.degree(),
+        };
+        topology.cells().len().saturating_sub(zone)
+    }
+    /// The shape of the board
+    pub fn topology(&self) -> Box<dyn Topology> {
+        match self.grid {
+            topology::Grid::Rectangle => Box::new(topology::Rectangle::new(self.width, self.height)),
+            topology::Grid::Torus => Box::new(topology::Torus::new(self.width, self.height)),
+            topology::Grid::Hex => Box::new(topology::Hex::new(self.width, self.height)),
+            topology::Grid::Triangle(neighborhood) => Box::new(
+                topology::Triangle::new(self.width, self.height, neighborhood)
+            ),
+            topology::Grid::Cube => Box::new(
+                topology::Cube::new(self.width, self.height, self.depth)
+            ),
+        }
+    }
+    /// Parse `width height mines [seed]` after the program name
+    pub fn init(args: &Vec<String>) -> Result<GameConfig, MinesweeperError> {
+        if args.len() != 4 && args.len() != 5 {
+            return Err(MinesweeperError::ArgumentCount { found: args.len().saturating_sub(1) });
         }
         let width = args[1]
             .parse::<usize>()
-            .or_else(|_|{Err("Failed to parse width")})?;
+            .map_err(|_| MinesweeperError::Parse { field: "width" })?;
         let height = args[2]
             .parse::<usize>()
-            .or_else(|_|{Err("Failed to parse height")})?;
+            .map_err(|_| MinesweeperError::Parse { field: "height" })?;
         let mines = args[3]
             .parse::<usize>()
-            .or_else(|_|{Err("Failed to parse mines")})?;
-        Ok(GameConfig { width, height, mines })
+            .map_err(|_| MinesweeperError::Parse { field: "mines" })?;
+        let mut config = GameConfig::new(width, height, mines);
+        if let Some(seed) = args.get(4) {
+            config.seed = Some(
+                seed.parse::<u64>()
+                    .map_err(|_| MinesweeperError::Parse { field: "seed" })?
+            );
+        }
+        Ok(config)
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 struct Answer {
     board: Vec<Vec<State>>,
 }
+#[derive(Clone, Serialize, Deserialize)]
 struct Board {
     board: Vec<Vec<DisplayStatus>>,
     total_count: usize,
+    /// Flags on the board, recounted when a game is loaded
+    #[serde(default)]
+    flags: usize,
 }
+#[derive(Serialize, Deserialize)]
 pub struct Game {
     answer: Answer,
     board: Board,
     config: GameConfig,
     pub status: GameStatus,
+    mines_placed: bool,
+    seed: u64,
+    hints: usize,
+    #[serde(skip)]
+    last_hint: Option<solver::Hint>,
+    /// Boards before each move, the last one is undone first
+    #[serde(skip)]
+    history: Vec<Snapshot>,
+    /// Boards of the moves undone, the last one is redone first
+    #[serde(skip)]
+    future: Vec<Snapshot>,
+    /// Every move so far, see `replay`
+    #[serde(default)]
+    steps: Vec<replay::Step>,
+    /// When the first move was played, moved back after a load
+    /// so that the times go on from the last recorded move
+    #[serde(skip)]
+    clock: Option<Instant>,
+    /// Clicks played so far, see `analysis`
+    #[serde(default)]
+    clicks: analysis::Clicks,
+    #[serde(default)]
+    timer: timer::Timer,
+    /// A paused game hides its board and takes no move
+    #[serde(default)]
+    paused: bool,
+    /// The mine that lost the game
+    #[serde(default)]
+    hit: Option<(usize, usize)>,
+    /// The plaque drawn at the top left, moved by `scroll`
+    #[serde(default)]
+    viewport: (usize, usize),
+    /// The layer drawn of a cube board
+    #[serde(default)]
+    layer: usize,
+    /// The shape of `config`, built once rather than on every move
+    #[serde(skip, default = "no_topology")]
+    topology: Box<dyn Topology>,
+    /// Plaques of `topology`
+    #[serde(skip)]
+    cells: usize,
 }
+
+/// Stands in for the shape of a game being read, see `Game::load`
+fn no_topology() -> Box<dyn Topology> {
+    Box::new(topology::Rectangle::new(0, 0))
+}
+
+/// Version of the save file format, see `versioned`
+const SAVE_VERSION: u32 = 1;
+
+/// What is written to disk by `Game::save`
+#[derive(Serialize, Deserialize)]
+struct SaveFile<G> {
+    version: u32,
+    game: G,
+}
+/// What a move changes, kept for undo and redo
+struct Snapshot {
+    board: Board,
+    status: GameStatus,
+    hit: Option<(usize, usize)>,
+}
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub struct Input {
     x: usize,
     y: usize,
@@ -79,68 +310,171 @@ impl Answer {
             board: vec![vec![State::Empty(0); w]; h],
         }
     }
-    fn init(config: &GameConfig) -> Result<Answer, &'static str> {
-        let (width, height) = (config.width, config.height);
+    /// Create an empty answer, mines are placed later by `place`
+    fn init(config: &GameConfig, topology: &dyn Topology) -> Result<Answer, MinesweeperError> {
+        if config.depth > 1 && config.grid != topology::Grid::Cube {
+            return Err(MinesweeperError::LayersWithoutCube { depth: config.depth });
+        }
+        let (height, width) = topology.size();
         let mine = config.mines;
-        if mine > width * height {
-            return Err("Too many mines");
+        let capacity = config.capacity(topology);
+        if width * height > 0 && mine > capacity {
+            return Err(MinesweeperError::TooManyMines {
+                requested: mine,
+                capacity,
+            });
+        }
+        if config.no_guess && mine * 100 > capacity * NO_GUESS_DENSITY {
+            return Err(MinesweeperError::TooDenseForNoGuess {
+                requested: mine,
+                capacity: capacity * NO_GUESS_DENSITY / 100,
+            });
+        }
+        return Ok(Answer::new(width, height));
+    }
+    /// Place the mines around the first click at (x, y)
+    /// The same seed and first click always give the same layout
+    fn place(
+        &mut self,
+        config: &GameConfig,
+        topology: &dyn Topology,
+        x: usize, y: usize,
+        seed: u64
+    ) -> Result<(), MinesweeperError> {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let zone = safe_zone(config, topology, x, y);
+        let attempts = if config.no_guess { NO_GUESS_ATTEMPTS } else { 1 };
+        let (height, width) = topology.size();
+        for _ in 0..attempts {
+            *self = Answer::new(width, height);
+            randgen_mine(self, topology, config.mines, &zone, &mut rng);
+            count_mine(self, topology);
+            if !config.no_guess || no_guess(self, config, x, y) {
+                return Ok(());
+            }
         }
-        let mut answer = Answer::new(width, height);
-        randgen_mine(&mut answer, config);
-        count_mine(&mut answer, config);
-        return Ok(answer);
+        Err(MinesweeperError::NoGuessFailed { attempts })
     }
 }
 
 impl Board {
-    fn new(config: &GameConfig) -> Board {
-        let (width, height) = (config.width, config.height);
+    fn new(topology: &dyn Topology) -> Board {
+        let (height, width) = topology.size();
         Board {
             board: vec![vec![DisplayStatus::Hidden; width]; height],
             total_count: 0,
+            flags: 0,
         }
     }
-    fn toggle(&mut self, x: usize, y: usize) -> Result<(), &'static str> {
+    /// Cycle the mark of a hidden plaque, the question mark
+    /// comes after the flag when `questions` is on.
+    /// Flags only go on hidden plaques, so there are never more of them
+    /// than plaques left hidden
+    fn toggle(&mut self, x: usize, y: usize, questions: bool) -> Result<(), MinesweeperError> {
         match self.board[x][y] {
             DisplayStatus::Hidden => {
                 self.board[x][y] = DisplayStatus::Flagged;
+                self.flags += 1;
+            },
+            DisplayStatus::Flagged if questions => {
+                self.board[x][y] = DisplayStatus::Questioned;
+                self.flags -= 1;
             },
             DisplayStatus::Flagged => {
                 self.board[x][y] = DisplayStatus::Hidden;
+                self.flags -= 1;
+            },
+            DisplayStatus::Questioned => {
+                self.board[x][y] = DisplayStatus::Hidden;
             },
-            _ => {return Err("Cannot toggle visible plaque");},
+            _ => {return Err(MinesweeperError::AlreadyRevealed { x, y });},
         };
         Ok(())
     }
-    fn show_line<T>(&self, x: usize, buffer: &mut T)
+    fn count_flags(&self) -> usize {
+        self.board
+            .iter()
+            .flatten()
+            .filter(|&&d| d == DisplayStatus::Flagged)
+            .count()
+    }
+    /// Write row x after `indent` spaces
+    fn show_line<T>(&self, x: usize, indent: usize, buffer: &mut T)
     where T: Write {
         let hid: Vec<u8> = " * ".as_bytes().to_vec();
         let fla: Vec<u8> = " F ".as_bytes().to_vec();    
+        let que: Vec<u8> = " ? ".as_bytes().to_vec();
+        let min: Vec<u8> = " M ".as_bytes().to_vec();
+        let exp: Vec<u8> = " X ".as_bytes().to_vec();
+        let wro: Vec<u8> = " W ".as_bytes().to_vec();
         buffer.flush().unwrap(); // clear the buffer
+        buffer.write_all(" ".repeat(indent).as_bytes()).unwrap();
         for plaque in &self.board[x] {
             match plaque {
                 DisplayStatus::Hidden =>
                     buffer.write(&hid).unwrap(),
                 DisplayStatus::Flagged =>
                     buffer.write(&fla).unwrap(),
+                DisplayStatus::Questioned =>
+                    buffer.write(&que).unwrap(),
+                // Counts go up to 26 on a cube
                 DisplayStatus::Visible(c) =>
                     buffer.write(
-                        format!(" {} ", c).as_bytes()
+                        format!("{:>2} ", c).as_bytes()
                     ).unwrap(),
+                DisplayStatus::Mine =>
+                    buffer.write(&min).unwrap(),
+                DisplayStatus::Exploded =>
+                    buffer.write(&exp).unwrap(),
+                DisplayStatus::WrongFlag =>
+                    buffer.write(&wro).unwrap(),
+            };
+        }
+        buffer.write_all(b"\n").unwrap();
+    }
+    /// Write row x of a triangle board, every plaque
+    /// between the `/` and `\\` of its triangle
+    fn show_triangles<T>(&self, x: usize, buffer: &mut T)
+    where T: Write {
+        let row = &self.board[x];
+        for (y, plaque) in row.iter().enumerate() {
+            let edge = if topology::Triangle::up(x, y) { '/' } else { '\\' };
+            let mark = match plaque {
+                DisplayStatus::Hidden => "*".to_string(),
+                DisplayStatus::Flagged => "F".to_string(),
+                DisplayStatus::Questioned => "?".to_string(),
+                DisplayStatus::Visible(c) => c.to_string(),
+                DisplayStatus::Mine => "M".to_string(),
+                DisplayStatus::Exploded => "X".to_string(),
+                DisplayStatus::WrongFlag => "W".to_string(),
             };
+            // Counts go up to 12 with the corners
+            buffer.write_all(format!("{}{:>2}", edge, mark).as_bytes()).unwrap();
         }
-        println!();
+        if let Some(y) = row.len().checked_sub(1) {
+            let edge = if topology::Triangle::up(x, y) { "\\" } else { "/" };
+            buffer.write_all(edge.as_bytes()).unwrap();
+        }
+        buffer.write_all(b"\n").unwrap();
     }
     fn show_board(&self, buffer: &mut Vec<String>) {
         let hid: String = "".to_string();
         let fla: String = "&#128681".to_string();
+        let que: String = "?".to_string();
+        let min: String = "&#128163".to_string();
+        let exp: String = "&#128165".to_string();
+        let wro: String = "&#10060".to_string();
         buffer.clear();
         for line in self.board.iter() {
             for p in line.iter() {
                 match p {
                     DisplayStatus::Hidden => buffer.push(hid.clone()),
                     DisplayStatus::Flagged => buffer.push(fla.clone()),
+                    DisplayStatus::Questioned => buffer.push(que.clone()),
                     DisplayStatus::Visible(c) => buffer.push(c.to_string()),
+                    DisplayStatus::Mine => buffer.push(min.clone()),
+                    DisplayStatus::Exploded => buffer.push(exp.clone()),
+                    DisplayStatus::WrongFlag => buffer.push(wro.clone()),
                 }
             }
         }
@@ -148,136 +482,649 @@ impl Board {
 }
 
 impl Game {
-    pub fn init(config: GameConfig) -> Result<Game, &'static str> {
-        let answer = Answer::init(&config)?;
-        let board = Board::new(&config);
+    pub fn init(config: GameConfig) -> Result<Game, MinesweeperError> {
+        let topology = config.topology();
+        let answer = Answer::init(&config, &*topology)?;
+        let board = Board::new(&*topology);
         let status = GameStatus::InProgress;
+        let seed = config.seed.unwrap_or_else(rand::random);
         Ok(Game {
             answer,
             board,
             config,
             status,
+            mines_placed: false,
+            seed,
+            hints: 0,
+            last_hint: None,
+            history: vec![],
+            future: vec![],
+            steps: vec![],
+            clock: None,
+            clicks: analysis::Clicks::default(),
+            timer: timer::Timer::default(),
+            paused: false,
+            hit: None,
+            viewport: (0, 0),
+            layer: 0,
+            cells: topology.cells().len(),
+            topology,
         })
     }
+    /// Start a game on a layout whose mines are already placed
+    fn from_answer(answer: Answer, config: GameConfig) -> Game {
+        let topology = config.topology();
+        Game {
+            answer,
+            board: Board::new(&*topology),
+            config,
+            status: GameStatus::InProgress,
+            mines_placed: true,
+            seed: 0,
+            hints: 0,
+            last_hint: None,
+            history: vec![],
+            future: vec![],
+            steps: vec![],
+            clock: None,
+            clicks: analysis::Clicks::default(),
+            timer: timer::Timer::default(),
+            paused: false,
+            hit: None,
+            viewport: (0, 0),
+            layer: 0,
+            cells: topology.cells().len(),
+            topology,
+        }
+    }
     pub fn placehold() -> Game {
-        let config = GameConfig{width: 0, height: 0, mines: 0};
+        let config = GameConfig::new(0, 0, 0);
         Game::init(config).unwrap()
     }
-    pub fn init_ref(config: GameConfig, game: &mut Game) -> Result<(), &'static str> {
+    pub fn init_ref(config: GameConfig, game: &mut Game) -> Result<(), MinesweeperError> {
         *game = Game::init(config)?;
         Ok(())
     }
-    pub fn try_update(&mut self, input: &Input) -> Result<(), &'static str> {
+    pub fn try_update(&mut self, input: &Input) -> Result<(), MinesweeperError> {
+        if self.paused {
+            return Err(MinesweeperError::Paused);
+        }
+        self.record(replay::Event::Input(*input));
+        self.last_hint = None;
+        if input.act == Action::Hint {
+            self.last_hint = Some(
+                self.hint().ok_or(MinesweeperError::NoCertainMove)?
+            );
+            return Ok(());
+        }
+        let before = self.snapshot();
+        let applied = self.apply(input);
+        let changed = input.act == Action::RightClick
+            || self.board.total_count != before.board.total_count
+            || self.status != before.status;
+        self.clicks.add(input.act, applied.is_ok() && changed);
+        applied?;
+        if input.act == Action::Click {
+            self.timer.start();
+        }
+        self.sync_timer();
+        self.history.push(before);
+        self.future.clear();
+        Ok(())
+    }
+    /// Start a game on a plain-text layout, see `layout`
+    pub fn from_layout(text: &str) -> Result<Game, MinesweeperError> {
+        let (answer, config) = layout::parse_answer(text)?;
+        Ok(Game::from_answer(answer, config))
+    }
+    /// Replace what the player sees with a plain-text view,
+    /// it must have the size of the layout
+    pub fn with_view(mut self, text: &str) -> Result<Game, MinesweeperError> {
+        let (board, _) = layout::parse_view(text)?;
+        let width = board.board.first().map_or(0, |row| row.len());
+        let (rows, columns) = self.topology.size();
+        if (width, board.board.len()) != (columns, rows) {
+            return Err(MinesweeperError::Layout {
+                line: 1,
+                message: format!(
+                    "the view is {}x{}, the layout is {}x{}",
+                    width, board.board.len(),
+                    columns, rows
+                ),
+            });
+        }
+        self.board = board;
+        Ok(self)
+    }
+    /// Where the mines are, as a plain-text layout.
+    /// Empty until the first click has placed them
+    pub fn layout(&self) -> String {
+        layout::write_answer(&self.answer)
+    }
+    /// What the player sees, as a plain-text view
+    pub fn view(&self) -> String {
+        layout::write_view(&self.board, self.config.mines)
+    }
+    /// Write the game to `path`, the undo history is not kept
+    pub fn save(&self, path: &Path) -> Result<(), MinesweeperError> {
+        versioned::write(path, &SaveFile { version: SAVE_VERSION, game: self })
+    }
+    /// Read a game written by `save`
+    pub fn load(path: &Path) -> Result<Game, MinesweeperError> {
+        let mut file: SaveFile<Game> = versioned::read(path, SAVE_VERSION)?;
+        file.game.board.flags = file.game.board.count_flags();
+        file.game.reshape();
+        // The clock is read back held, the game waits for `resume`
+        if file.game.timer.running() {
+            file.game.paused = true;
+        }
+        Ok(file.game)
+    }
+    /// Take back the last move, a flood fill is taken back as a whole.
+    /// A won or lost game can only be taken back in practice mode
+    pub fn undo(&mut self) -> Result<(), MinesweeperError> {
+        if self.paused {
+            return Err(MinesweeperError::Paused);
+        }
+        self.record(replay::Event::Undo);
+        if self.status != GameStatus::InProgress && !self.config.practice {
+            return Err(MinesweeperError::UndoAfterEnd);
+        }
+        let before = self.history.pop().ok_or(MinesweeperError::NothingToUndo)?;
+        let after = self.snapshot();
+        self.restore(before);
+        self.sync_timer();
+        self.future.push(after);
+        Ok(())
+    }
+    /// Play the last move taken back again
+    pub fn redo(&mut self) -> Result<(), MinesweeperError> {
+        if self.paused {
+            return Err(MinesweeperError::Paused);
+        }
+        self.record(replay::Event::Redo);
+        let after = self.future.pop().ok_or(MinesweeperError::NothingToRedo)?;
+        let before = self.snapshot();
+        self.restore(after);
+        self.sync_timer();
+        self.history.push(before);
+        Ok(())
+    }
+    /// Measures of the layout, `None` before the mines are placed
+    pub fn metrics(&self) -> Option<analysis::Metrics> {
+        self.mines_placed.then(|| analysis::metrics(&self.answer, &*self.topology))
+    }
+    pub fn clicks(&self) -> analysis::Clicks {
+        self.clicks
+    }
+    /// How well the game was played, `None` while it is in progress
+    pub fn efficiency(&self) -> Option<analysis::Efficiency> {
+        if self.status == GameStatus::InProgress {
+            return None;
+        }
+        Some(analysis::efficiency(
+            analysis::metrics(&self.answer, &*self.topology).bbbv,
+            analysis::solved_bbbv(&self.answer, &self.board, &*self.topology),
+            self.elapsed(),
+            self.clicks,
+        ))
+    }
+    /// The board of a lost game with every mine, the one hit
+    /// and the wrong flags, `None` unless the game is lost
+    fn loss_board(&self) -> Option<Board> {
+        if self.status != GameStatus::Lost {
+            return None;
+        }
+        let mut board = self.board.clone();
+        for (x, row) in board.board.iter_mut().enumerate() {
+            for (y, plaque) in row.iter_mut().enumerate() {
+                *plaque = match (self.answer.board[x][y], *plaque) {
+                    (State::Mine, _) if self.hit == Some((x, y)) => DisplayStatus::Exploded,
+                    (State::Mine, DisplayStatus::Flagged) => DisplayStatus::Flagged,
+                    (State::Mine, _) => DisplayStatus::Mine,
+                    (State::Empty(_), DisplayStatus::Flagged) => DisplayStatus::WrongFlag,
+                    (State::Empty(_), p) => p,
+                };
+            }
+        }
+        Some(board)
+    }
+    /// What `show_str` gives for a lost game, `None` unless the game is lost
+    pub fn loss_view(&self) -> Option<Vec<String>> {
+        (self.status == GameStatus::Lost).then(|| {
+            let mut buffer = vec![];
+            self.show_str(&mut buffer);
+            buffer
+        })
+    }
+    /// The board as it is drawn: hidden while paused, with the mines
+    /// after a loss, and seen from the viewport
+    fn shown(&self) -> Board {
+        let mut board = if self.paused {
+            Board::new(&*self.topology)
+        } else {
+            self.loss_board().unwrap_or_else(|| self.board.clone())
+        };
+        board.board = self.drawn(&board.board);
+        board
+    }
+    /// The part of a grid of the whole board that is drawn:
+    /// the current layer seen from the viewport
+    pub fn drawn<T: Clone>(&self, grid: &[Vec<T>]) -> Vec<Vec<T>> {
+        let start = (self.layer * self.config.height).min(grid.len());
+        let end = (start + self.config.height).min(grid.len());
+        topology::shift(&grid[start..end], self.viewport)
+    }
+    /// The layer drawn, counted from 0
+    pub fn layer(&self) -> usize {
+        self.layer
+    }
+    /// The layer of the plaques in row x of the board
+    pub fn layer_of(&self, x: usize) -> usize {
+        x.checked_div(self.config.height).unwrap_or(0)
+    }
+    /// Draw another layer of a cube board
+    pub fn set_layer(&mut self, layer: usize) -> Result<(), MinesweeperError> {
+        if layer >= self.config.layers() {
+            return Err(MinesweeperError::NoLayer { layers: self.config.layers() });
+        }
+        self.layer = layer;
+        Ok(())
+    }
+    /// Draw the layer `layers` above or below the one drawn,
+    /// negative towards the first layer
+    pub fn turn_layer(&mut self, layers: isize) -> Result<(), MinesweeperError> {
+        let layer = self.layer
+            .checked_add_signed(layers)
+            .ok_or(MinesweeperError::NoLayer { layers: self.config.layers() })?;
+        self.set_layer(layer)
+    }
+    /// Move the viewport of a wrap-around board by whole rows and columns,
+    /// what leaves one edge comes back on the other
+    pub fn scroll(&mut self, rows: isize, columns: isize) -> Result<(), MinesweeperError> {
+        if self.config.grid != topology::Grid::Torus {
+            return Err(MinesweeperError::NoWrap);
+        }
+        let (height, width) = self.topology.size();
+        if width * height == 0 {
+            return Ok(());
+        }
+        self.viewport = (
+            (self.viewport.0 as isize + rows).rem_euclid(height as isize) as usize,
+            (self.viewport.1 as isize + columns).rem_euclid(width as isize) as usize,
+        );
+        Ok(())
+    }
+    /// The plaque drawn at the top left
+    pub fn viewport(&self) -> (usize, usize) {
+        self.viewport
+    }
+    /// The plaque drawn at (x, y) of the viewport, on the layer drawn.
+    /// A position off the layer stays off the board: the rows under
+    /// the layer belong to the other layers, so it moves past the last row
+    pub fn from_viewport(&self, x: usize, y: usize) -> (usize, usize) {
+        let (height, width) = (self.config.height, self.config.width);
+        if x >= height {
+            return (x - height + self.topology.size().0, y);
+        }
+        if y >= width {
+            return (x, y);
+        }
+        (
+            self.layer * height + (x + self.viewport.0) % height,
+            (y + self.viewport.1) % width,
+        )
+    }
+    /// How the plaque at (x, y) is named to the player: where it is
+    /// drawn in the viewport, and on a cube board its layer from 1
+    pub fn place(&self, x: usize, y: usize) -> String {
+        let (vx, vy) = self.to_viewport(x, y);
+        if self.config.layers() > 1 {
+            format!("({}, {}) on layer {}", vx, vy, self.layer_of(x) + 1)
+        } else {
+            format!("({}, {})", vx, vy)
+        }
+    }
+    /// Where the plaque at (x, y) is drawn in the viewport
+    /// when its layer is drawn, see `layer_of`
+    pub fn to_viewport(&self, x: usize, y: usize) -> (usize, usize) {
+        let (height, width) = (self.config.height, self.config.width);
+        if !self.topology.contains(x, y) {
+            return (x, y);
+        }
+        ((x % height + height - self.viewport.0) % height, (y + width - self.viewport.1) % width)
+    }
+    /// Read a move typed on the terminal, whose coordinates are those
+    /// of the board drawn by `show`, see `input`.
+    /// `<` and `>` draw another layer of a cube board and give no move
+    pub fn input<T>(&mut self, i: T) -> Result<Option<Input>, MinesweeperError>
+    where T: BufRead {
+        let line = read_line(i);
+        match line.trim() {
+            "<" => return self.turn_layer(-1).map(|_| None),
+            ">" => return self.turn_layer(1).map(|_| None),
+            _ => (),
+        }
+        let mut typed = parse_input(&line)?;
+        if matches!(typed.act, Action::Click | Action::RightClick | Action::Chord) {
+            (typed.x, typed.y) = self.from_viewport(typed.x, typed.y);
+        }
+        Ok(Some(typed))
+    }
+    /// Mines not flagged yet, negative when there are more flags than mines
+    pub fn mines_remaining(&self) -> isize {
+        self.config.mines as isize - self.board.flags as isize
+    }
+    /// Milliseconds on the clock, from the first reveal to the end of the game
+    pub fn elapsed(&self) -> u64 {
+        self.timer.elapsed()
+    }
+    /// Stop the clock and hide the board until `resume`
+    pub fn pause(&mut self) {
+        if self.status == GameStatus::InProgress {
+            self.paused = true;
+            self.timer.hold();
+        }
+    }
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.timer.release();
+    }
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+    /// The clock runs while the game is in progress, a loss taken back
+    /// in practice mode starts it again
+    fn sync_timer(&mut self) {
+        if self.status == GameStatus::InProgress {
+            self.timer.reopen();
+        } else {
+            self.timer.stop();
+        }
+    }
+    /// Everything needed to play this game again
+    pub fn replay(&self) -> replay::Replay {
+        replay::Replay::new(self)
+    }
+    fn record(&mut self, event: replay::Event) {
+        let now = Instant::now();
+        let last = Duration::from_millis(self.steps.last().map_or(0, |s| s.time));
+        let clock = *self.clock.get_or_insert_with(|| now.checked_sub(last).unwrap_or(now));
+        self.steps.push(replay::Step {
+            time: now.duration_since(clock).as_millis() as u64,
+            event,
+        });
+    }
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            board: self.board.clone(),
+            status: self.status,
+            hit: self.hit,
+        }
+    }
+    /// Build the shape of `config` again, once it is read or changed
+    fn reshape(&mut self) {
+        self.topology = self.config.topology();
+        self.cells = self.topology.cells().len();
+    }
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.board = snapshot.board;
+        self.status = snapshot.status;
+        self.hit = snapshot.hit;
+    }
+    fn apply(&mut self, input: &Input) -> Result<(), MinesweeperError> {
+        if self.status != GameStatus::InProgress {
+            return Err(MinesweeperError::GameOver);
+        }
         let (x, y) = (input.x, input.y);
-        if !on_board(
-                self.config.width,
-                self.config.height,
-                x as i32, y as i32
-            ) {
-            return Err("Out of bound");
+        if !self.topology.contains(x, y) {
+            return Err(MinesweeperError::OutOfBounds { x, y });
+        }
+        if input.act == Action::Click
+            && !self.mines_placed
+            && matches!(self.board.board[x][y], DisplayStatus::Hidden | DisplayStatus::Questioned) {
+            self.answer.place(&self.config, &*self.topology, x, y, self.seed)?;
+            self.mines_placed = true;
         }
         let cur_answer = self.answer.board[x][y];
         let cur_visible: DisplayStatus = self.board.board[x][y];
         if input.act == Action::RightClick {
-            self.board.toggle(x, y)?;
+            self.board.toggle(x, y, self.config.questions)?;
+        } else if input.act == Action::Chord {
+            self.chord(x, y)?;
         } else {
             match cur_answer {
                 State::Mine => {
                     if cur_visible == DisplayStatus::Flagged {
-                        return Err("Cannot click flagged plaque");
+                        return Err(MinesweeperError::Flagged { x, y });
                     } else {
                         self.status = GameStatus::Lost;
+                        self.hit = Some((x, y));
                     }
                 },
                 State::Empty(_) => {
                     match cur_visible {
-                        DisplayStatus::Visible(_) => {
-                            return Err("Cannot click visible plaque");
+                        DisplayStatus::Visible(_)
+                            | DisplayStatus::Mine
+                            | DisplayStatus::Exploded => {
+                            return Err(MinesweeperError::AlreadyRevealed { x, y });
                         },
-                        DisplayStatus::Flagged => {
-                            return Err("Cannot click flagged plaque");
+                        DisplayStatus::Flagged | DisplayStatus::WrongFlag => {
+                            return Err(MinesweeperError::Flagged { x, y });
                         },
-                        DisplayStatus::Hidden => {
+                        DisplayStatus::Hidden | DisplayStatus::Questioned => {
                             self.update(x, y);
                         },
                     }
                 },
             }
         }
-        if self.check_win() {
+        if self.status != GameStatus::Lost && self.check_win() {
             self.status = GameStatus::Won;
         }
         Ok(())
     }
+    /// Reveal every hidden neighbor of the visible number at (x, y)
+    /// once the number of flags around it matches the number.
+    /// A wrong flag means a mine gets revealed and the game is lost.
+    fn chord(&mut self, x: usize, y: usize) -> Result<(), MinesweeperError> {
+        let c = match self.board.board[x][y] {
+            DisplayStatus::Visible(c) => c as usize,
+            _ => return Err(MinesweeperError::NotRevealed { x, y }),
+        };
+        let neighbors = self.topology.neighbors(x, y);
+        let flags = neighbors
+            .iter()
+            .filter(|&&(nx, ny)| self.board.board[nx][ny] == DisplayStatus::Flagged)
+            .count();
+        if flags != c {
+            return Err(MinesweeperError::FlagMismatch { x, y, number: c as u8, flags });
+        }
+        for (nx, ny) in neighbors {
+            if !matches!(self.board.board[nx][ny], DisplayStatus::Hidden | DisplayStatus::Questioned) {
+                continue;
+            }
+            match self.answer.board[nx][ny] {
+                State::Mine => {
+                    self.status = GameStatus::Lost;
+                    self.hit.get_or_insert((nx, ny));
+                },
+                State::Empty(_) => self.update(nx, ny),
+            }
+        }
+        Ok(())
+    }
+    /// Every plaque the visible board proves safe or a mine
+    pub fn deduce(&self) -> solver::Deduction {
+        solver::solve(&self.board, &*self.topology, self.config.mines)
+    }
+    /// One plaque the player can settle, with the reason.
+    /// Every hint given is counted, see `hints_used`
+    pub fn hint(&mut self) -> Option<solver::Hint> {
+        let hint = if self.mines_placed {
+            solver::hint(
+                &self.board,
+                &*self.topology,
+                self.config.mines,
+                &|(x, y)| self.place(x, y),
+            )?
+        } else {
+            let (x, y) = (self.config.height / 2, self.config.width / 2);
+            if !self.topology.contains(x, y) {
+                return None;
+            }
+            solver::Hint {
+                x, y,
+                mine: false,
+                reason: "the first click is always safe".to_string(),
+            }
+        };
+        self.hints += 1;
+        Some(hint)
+    }
+    /// How many hints were given in this game
+    pub fn hints_used(&self) -> usize {
+        self.hints
+    }
+    /// The hint asked for by the last input, if it was `Action::Hint`
+    pub fn last_hint(&self) -> Option<&solver::Hint> {
+        self.last_hint.as_ref()
+    }
+    /// The chance of a mine under every plaque, `None` where revealed
+    pub fn probabilities(&self) -> Vec<Vec<Option<f64>>> {
+        solver::probabilities(&self.board, &*self.topology, self.config.mines)
+    }
+    /// The seed the board is generated from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
     pub fn show<T>(&self, buffer: &mut T)
     where T: Write {
-        for (i, _) in self.board.board.iter().enumerate() {
-            self.board.show_line(i, buffer);
+        let board = self.shown();
+        let topology = &self.topology;
+        // The rows drawn are those of one layer
+        for (i, _) in board.board.iter().enumerate() {
+            match self.config.grid {
+                topology::Grid::Triangle(_) => board.show_triangles(i, buffer),
+                _ => board.show_line(i, topology.indent(i), buffer),
+            }
+        }
+        if self.config.layers() > 1 {
+            buffer.write_all(
+                format!("Layer {} of {}, < and > to move\n", self.layer + 1, self.config.layers())
+                    .as_bytes()
+            ).unwrap();
+        }
+        if self.paused {
+            return;
+        }
+        buffer.write_all(format!("Mines left: {}\n", self.mines_remaining()).as_bytes()).unwrap();
+        if let Some(hint) = &self.last_hint {
+            buffer.write_all(
+                format!("Hint: {} is {}, {}\n",
+                    self.place(hint.x, hint.y),
+                    if hint.mine { "a mine" } else { "safe" },
+                    hint.reason
+                ).as_bytes()
+            ).unwrap();
         }
     }
     pub fn show_str(&self, buffer: &mut Vec<String>) {
-        self.board.show_board(buffer);
+        self.shown().show_board(buffer);
     }
     fn check_win(&self) -> bool {
-        self.board.total_count
-            == self.config.width*self.config.height
-            - self.config.mines
-    }
-    fn update(&mut self, x: usize, y: usize){
-        let cur_answer = self.answer.board[x][y].clone();
-        let cur_state = self.board.board[x][y].clone();
-        match cur_answer {
-            State::Mine => return,
-            State::Empty(c) => {
-                if cur_state == DisplayStatus::Visible(c)
-                    || cur_state == DisplayStatus::Flagged {
-                    return;
-                } // If already visible or flagged, do nothing
-                // Otherwise, update the board
-                self.board.board[x][y] = DisplayStatus::Visible(c);
-                self.board.total_count += 1;
-                // If the plaque is empty, update all its neighbors
-                if c == 0 {
-                    for (nx, ny) in next_on_board(
-                            self.config.width,
-                            self.config.height,
-                            x, y
-                        ) {
-                        self.update(nx, ny);
+        self.board.total_count == self.cells - self.config.mines
+    }
+    fn update(&mut self, x: usize, y: usize) {
+        self.flood(x, y);
+    }
+    /// Reveal (x, y) and, from every zero revealed, its neighbors.
+    /// A worklist rather than recursion, a large opening would overflow the stack
+    fn flood(&mut self, x: usize, y: usize) {
+        let mut work = vec![(x, y)];
+        while let Some((x, y)) = work.pop() {
+            let cur_answer = self.answer.board[x][y].clone();
+            let cur_state = self.board.board[x][y].clone();
+            match cur_answer {
+                State::Mine => continue,
+                State::Empty(c) => {
+                    if cur_state == DisplayStatus::Visible(c)
+                        || cur_state == DisplayStatus::Flagged {
+                        continue;
+                    } // If already visible or flagged, do nothing,
+                    // a question mark is revealed like a hidden plaque
+                    // Otherwise, update the board
+                    self.board.board[x][y] = DisplayStatus::Visible(c);
+                    self.board.total_count += 1;
+                    // If the plaque is empty, update all its neighbors
+                    if c == 0 {
+                        work.extend(self.topology.neighbors(x, y));
                     }
-                }
-            },
-        };
+                },
+            };
+        }
     }
 }
 
-fn randgen_mine(answer: &mut Answer, config: &GameConfig) {
-    let mut rng = rand::thread_rng();
-    let (w, h, m) = (config.width, config.height, config.mines);
-    let mut plaque = (0..w*h).collect::<Vec<usize>>();
-    plaque.shuffle(&mut rng);
-    for co in 0..m {
-        let (x, y) = (plaque[co]/w, plaque[co]%w);
+/// Plaques that must stay free of mines when the first click is at (x, y)
+fn safe_zone(
+    config: &GameConfig,
+    topology: &dyn Topology,
+    x: usize, y: usize,
+) -> Vec<(usize, usize)> {
+    let mut zone = vec![(x, y)];
+    if config.first_click == FirstClick::Opening {
+        zone.extend(topology.neighbors(x, y));
+    }
+    zone
+}
+fn randgen_mine(
+    answer: &mut Answer,
+    topology: &dyn Topology,
+    mines: usize,
+    safe: &[(usize, usize)],
+    rng: &mut ChaCha8Rng,
+) {
+    let mut plaque = topology
+        .cells()
+        .into_iter()
+        .filter(|p| !safe.contains(p))
+        .collect::<Vec<(usize, usize)>>();
+    plaque.shuffle(rng);
+    for &(x, y) in plaque.iter().take(mines) {
         answer.board[x][y] = State::Mine;
     }
 }
-fn count_mine(answer: &mut Answer, config: &GameConfig) {
-    let (w, h) = (config.width, config.height);
-    for x in 0..h {
-        for y in 0..w {
-            if answer.board[x][y] == State::Mine {
-                continue;
-            }
-            let mut count = 0;
-            
-            for (nx, ny) in next_on_board(w, h, x, y) {
-                if answer.board[nx][ny]
-                    == State::Mine {
-                    count += 1;
-                }
+/// Whether the solver clears the whole layout from a click at (x, y)
+/// by only revealing plaques it proves safe
+fn no_guess(answer: &Answer, config: &GameConfig, x: usize, y: usize) -> bool {
+    let mut game = Game::from_answer(answer.clone(), *config);
+    game.update(x, y);
+    while !game.check_win() {
+        let deduction = game.deduce();
+        if deduction.safe.is_empty() {
+            return false;
+        }
+        for (sx, sy) in deduction.safe {
+            game.update(sx, sy);
+        }
+    }
+    true
+}
+fn count_mine(answer: &mut Answer, topology: &dyn Topology) {
+    for (x, y) in topology.cells() {
+        if answer.board[x][y] == State::Mine {
+            continue;
+        }
+        let mut count = 0;
+        
+        for (nx, ny) in topology.neighbors(x, y) {
+            if answer.board[nx][ny]
+                == State::Mine {
+                count += 1;
             }
-            answer.board[x][y] = State::Empty(count);
         }
+        answer.board[x][y] = State::Empty(count);
     }
 }
 pub fn win() {
@@ -292,57 +1139,54 @@ pub fn lose() {
 
 /// Read input from an input with `BufRead` trait
 /// e.g. `input(std::io::stdin().lock())`
-pub fn input<T>(mut i: T) -> Result<Input, &'static str>
+pub fn input<T>(i: T) -> Result<Input, MinesweeperError>
+where T: BufRead {
+    parse_input(&read_line(i))
+}
+
+/// Ask for a move and read one line of it
+fn read_line<T>(mut i: T) -> String
 where T: BufRead {
     let mut line = String::new();
     println!("Please input your action: ");
-    println!("x y action");
+    println!("x y action, a q r action for axial hex coordinates, or h for a hint");
+    println!("< and > move between the layers of a cube");
     i.read_line(&mut line)
         .expect("Failed to read line");
-    let mut iter = line.split_whitespace();
+    line
+}
+
+fn parse_input(line: &str) -> Result<Input, MinesweeperError> {
+    if line.trim() == "h" {
+        return Ok(Input{x: 0, y: 0, act: Action::Hint});
+    }
+    let mut iter = line.split_whitespace().peekable();
+    if iter.next_if_eq(&"a").is_some() {
+        let q: isize = iter.next()
+            .unwrap_or("")
+            .parse::<isize>()
+            .map_err(|_| MinesweeperError::Parse { field: "q" })?;
+        let r: usize = iter.next()
+            .unwrap_or("")
+            .parse::<usize>()
+            .map_err(|_| MinesweeperError::Parse { field: "r" })?;
+        let (x, y) = topology::Hex::from_axial(q, r)
+            .ok_or(MinesweeperError::Parse { field: "q" })?;
+        let act = Action::from_letter(iter.next().unwrap_or(""))?;
+        return Ok(Input{x, y, act});
+    }
     let x: usize = iter.next()
-        .unwrap()
+        .unwrap_or("")
         .parse::<usize>()
-        .or_else(|_|{Err("Failed to parse x")})?;
+        .map_err(|_| MinesweeperError::Parse { field: "x" })?;
     let y: usize = iter.next()
-        .unwrap()
+        .unwrap_or("")
         .parse::<usize>()
-        .or_else(|_|{Err("Failed to parse y")})?;
-    let act: Action = match iter.next().unwrap() {
-        "l" => Action::Click,
-        "r" => Action::RightClick,
-        _ => return Err("Invalid action"),
-    };
+        .map_err(|_| MinesweeperError::Parse { field: "y" })?;
+    let act: Action = Action::from_letter(iter.next().unwrap_or(""))?;
     Ok(Input{x, y, act})
 }
 
-fn on_board(
-    width: usize,
-    height: usize,
-    x: i32, y: i32
-) -> bool {
-    x >= 0 && x < height as i32 && y >= 0 && y < width as i32
-}
-
-fn next_on_board(
-    width: usize,
-    height: usize,
-    x: usize, y: usize
-) -> Vec<(usize, usize)> {
-    let steps: Vec<(i32, i32)> = vec![
-                (0, 1), (0, -1), (1, 0), (-1, 0),
-                (1, 1), (1, -1), (-1, 1), (-1, -1)
-            ];
-    let mut res: Vec<(usize, usize)> = vec![];
-    for (dx, dy) in steps {
-        let (nx, ny) = (x as i32 + dx, y as i32 + dy);
-        if on_board(width, height, nx, ny) {
-            res.push((nx as usize, ny as usize));
-        }
-    }
-    return res;
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -356,18 +1200,39 @@ mod tests {
     }
     #[test]
     fn config_test() {
-        let c = GameConfig{width: 10, height: 10, mines: 30};
+        let c = GameConfig::new(10, 10, 30);
         assert_eq!(c.width, 10);
         assert_eq!(c.height, 10);
         assert_eq!(c.mines, 30);
     }
     #[test]
     fn answer_test_1() {
-        let c = GameConfig{width: 10, height: 10, mines: 100};
-        let a: Answer = Answer::init(&c).unwrap();
+        let c = GameConfig::new(10, 10, 99);
+        let mut a: Answer = Answer::init(&c, &*c.topology()).unwrap();
         assert_eq!(a.board.len(), 10);
         assert_eq!(a.board[0].len(), 10);
+        assert_eq!(a.board[0][0], State::Empty(0));
+        a.place(&c, &*c.topology(), 0, 0, 0).unwrap();
+        assert_eq!(a.board[0][0], State::Empty(3));
+        assert_eq!(a.board[9][9], State::Mine);
+    }
+    #[test]
+    fn answer_test_2() {
+        let c = GameConfig::new(10, 10, 91)
+            .with_first_click(FirstClick::Opening);
+        let mut a: Answer = Answer::init(&c, &*c.topology()).unwrap();
+        a.place(&c, &*c.topology(), 5, 5, 0).unwrap();
+        assert_eq!(a.board[5][5], State::Empty(0));
+        for (x, y) in c.topology().neighbors(5, 5) {
+            assert_ne!(a.board[x][y], State::Mine);
+        }
         assert_eq!(a.board[0][0], State::Mine);
+        let c = GameConfig::new(10, 10, 92)
+            .with_first_click(FirstClick::Opening);
+        assert_eq!(
+            Answer::init(&c, &*c.topology()).err(),
+            Some(MinesweeperError::TooManyMines { requested: 92, capacity: 91 })
+        );
     }
 
     #[test]
@@ -385,10 +1250,10 @@ mod tests {
         let mut v_21 = v_2.clone();
         let mut a_1 = Answer{board: v_1};
         let mut a_2 = Answer{board: v_2};
-        count_mine(&mut a_1, &GameConfig{width: 3, height: 3, mines: 3});
+        count_mine(&mut a_1, &*GameConfig::new(3, 3, 3).topology());
         dbg!(a_1.board.clone());
         dbg!(a_2.board.clone());
-        count_mine(&mut a_2, &GameConfig{width: 3, height: 2, mines: 3});
+        count_mine(&mut a_2, &*GameConfig::new(3, 2, 3).topology());
         v_11 = vec![
             vec![State::Mine, State::Empty(2), State::Empty(1)],
             vec![State::Empty(2), State::Mine, State::Empty(2)],
@@ -402,7 +1267,60 @@ mod tests {
         assert_eq!(a_2.board, v_21);
     }
     #[test]
+    fn answer_seed_test() {
+        let c = GameConfig::new(4, 4, 4);
+        let mut a_1 = Answer::init(&c, &*c.topology()).unwrap();
+        let mut a_2 = Answer::init(&c, &*c.topology()).unwrap();
+        a_1.place(&c, &*c.topology(), 0, 0, 42).unwrap();
+        a_2.place(&c, &*c.topology(), 0, 0, 42).unwrap();
+        assert_eq!(a_1.board, a_2.board);
+        let v = vec![
+            vec![State::Empty(1), State::Mine, State::Empty(1), State::Empty(0)],
+            vec![State::Empty(2), State::Empty(2), State::Empty(1), State::Empty(0)],
+            vec![State::Mine, State::Empty(2), State::Empty(1), State::Empty(1)],
+            vec![State::Mine, State::Empty(2), State::Empty(1), State::Mine]
+        ];
+        assert_eq!(a_1.board, v);
+    }
+    #[test]
+    fn game_seed_test() {
+        let c = GameConfig::init(
+            &vec![
+                "".to_string(),
+                "4".to_string(),
+                "4".to_string(),
+                "4".to_string(),
+                "42".to_string()
+            ]
+        ).unwrap();
+        let mut g = Game::init(c).unwrap();
+        assert_eq!(g.seed(), 42);
+        g.try_update(&Input{x: 0, y: 0, act: Action::Click}).unwrap();
+        assert_eq!(g.answer.board[0][1], State::Mine);
+        assert_eq!(g.answer.board[2][0], State::Mine);
+        assert_eq!(g.board.board[0][0], DisplayStatus::Visible(1));
+    }
+    #[test]
+    fn answer_no_guess_test() {
+        let c = GameConfig::new(9, 9, 10)
+            .with_first_click(FirstClick::Opening)
+            .with_no_guess(true);
+        for seed in 0..10 {
+            let mut a = Answer::init(&c, &*c.topology()).unwrap();
+            a.place(&c, &*c.topology(), 4, 4, seed).unwrap();
+            assert!(no_guess(&a, &c, 4, 4));
+            let mines = a.board.iter().flatten().filter(|&&s| s == State::Mine).count();
+            assert_eq!(mines, 10);
+        }
+        let c = GameConfig::new(9, 9, 30).with_no_guess(true);
+        assert_eq!(
+            Answer::init(&c, &*c.topology()).err(),
+            Some(MinesweeperError::TooDenseForNoGuess { requested: 30, capacity: 17 })
+        );
+    }
+    #[test]
     fn next_steps_test() {
+        let next_on_board = |w, h, x, y| topology::Rectangle::new(w, h).neighbors(x, y);
         let v1 = next_on_board(3, 3, 0, 0);
         let v2 = next_on_board(3, 3, 1, 1);
         let v3 = next_on_board(3, 1, 0, 0);
@@ -424,52 +1342,57 @@ mod tests {
     }
     #[test]
     fn game_init_test_1() {
-        let c = GameConfig{width: 10, height: 10, mines: 100};
-        let g = Game::init(c).unwrap();
+        let c = GameConfig::new(10, 10, 99);
+        let mut g = Game::init(c).unwrap();
         assert_eq!(g.answer.board.len(), 10);
         assert_eq!(g.answer.board[0].len(), 10);
-        assert_eq!(g.answer.board[0][0], State::Mine);
         assert_eq!(g.board.board.len(), 10);
+        g.try_update(&Input{x: 0, y: 0, act: Action::Click}).unwrap();
+        assert_eq!(g.status, GameStatus::Won);
+        assert_eq!(g.answer.board[0][1], State::Mine);
     }
     #[test]
     fn game_init_test_2() {
-        let c = GameConfig{width: 10, height: 10, mines: 101};
+        let c = GameConfig::new(10, 10, 100);
         let g = Game::init(c).err();
-        assert_eq!(g, Some("Too many mines"));
+        assert_eq!(g, Some(MinesweeperError::TooManyMines { requested: 100, capacity: 99 }));
     }
     #[test]
     fn game_on_board_test() {
-        let w: usize = 10;
-        let h: usize = 10;
-        assert_eq!(on_board(w, h, 0, 0), true);
-        assert_eq!(on_board(w, h, 9, 9), true);
-        assert_eq!(on_board(w, h, 10, 10), false);
-        assert_eq!(on_board(w, h, -1, -1), false);
+        let board = GameConfig::new(10, 10, 0).topology();
+        assert!(board.contains(0, 0));
+        assert!(board.contains(9, 9));
+        assert!(!board.contains(10, 10));
+        assert!(!board.contains(usize::MAX, usize::MAX));
     }
     #[test]
     fn board_toggle_test() {
-        let mut b = Board::new(&GameConfig{width: 1, height: 2, mines: 0});
-        let mut _a = b.toggle(0, 0);
+        let mut b = Board::new(&*GameConfig::new(1, 2, 0).topology());
+        let mut _a = b.toggle(0, 0, false);
         assert_eq!(b.board[0][0], DisplayStatus::Flagged);
-        _a = b.toggle(1, 0);
+        _a = b.toggle(1, 0, false);
         assert_eq!(b.board[1][0], DisplayStatus::Flagged);
         assert_eq!(b.board[0][0], DisplayStatus::Flagged);
-        _a = b.toggle(0, 0);
+        _a = b.toggle(0, 0, false);
         assert_eq!(b.board[0][0], DisplayStatus::Hidden);
         assert_eq!(b.board[1][0], DisplayStatus::Flagged);
         b.board[0][0] = DisplayStatus::Visible(0);
-        assert_eq!(b.toggle(0, 0), Err("Cannot toggle visible plaque"));
+        assert_eq!(b.toggle(0, 0, false), Err(MinesweeperError::AlreadyRevealed { x: 0, y: 0 }));
+        _a = b.toggle(1, 0, true);
+        assert_eq!(b.board[1][0], DisplayStatus::Questioned);
+        _a = b.toggle(1, 0, true);
+        assert_eq!(b.board[1][0], DisplayStatus::Hidden);
     }
     #[test]
     fn game_update_test_1() {
-        let c = GameConfig{width: 1, height: 1, mines: 0};
+        let c = GameConfig::new(1, 1, 0);
         let mut g = Game::init(c).unwrap();
         g.update(0, 0);
         assert_eq!(g.board.board[0][0], DisplayStatus::Visible(0));
     }
     #[test]
     fn game_update_test_2() {
-        let c = GameConfig{width: 3, height: 3, mines: 0};
+        let c = GameConfig::new(3, 3, 0);
         let mut g = Game::init(c).unwrap();
         g.update(0, 0);
         let b = vec![
@@ -481,83 +1404,44 @@ mod tests {
     }
     #[test]
     fn game_update_test_3() {
-        let c = GameConfig{width: 3, height: 3, mines: 1};
-        let mut a = Answer::new(3, 3);
-        a.board[2][2] = State::Mine;
-        let mut g = Game {
-            answer: a,
-            board: Board::new(&c),
-            config: c,
-            status: GameStatus::InProgress,
-        };
+        let mut g = Game::from_layout("...\n...\n..*").unwrap();
         g.update(0, 0);
-        let b = vec![
-            vec![DisplayStatus::Visible(0), DisplayStatus::Visible(0), DisplayStatus::Visible(0)],
-            vec![DisplayStatus::Visible(0), DisplayStatus::Visible(1), DisplayStatus::Visible(1)],
-            vec![DisplayStatus::Visible(0), DisplayStatus::Visible(1), DisplayStatus::Hidden]
-        ];
+        let (b, _) = layout::parse_view("000\n011\n01#").unwrap();
+        assert_eq!(g.board.board, b.board);
+        assert_eq!(g.board.total_count, 8);
     }
     #[test]
     fn game_update_test_4() {
-        let c = GameConfig{width: 3, height: 3, mines: 1};
-        let mut a = Answer::new(3, 3);
-        a.board[1][1] = State::Empty(1);
-        a.board[1][2] = State::Empty(1);
-        a.board[2][1] = State::Empty(1);
-        a.board[2][2] = State::Mine;
-        let mut g = Game {
-            answer: a,
-            board: Board::new(&c),
-            config: c,
-            status: GameStatus::InProgress,
-        };
-        g.board.board[2][2] = DisplayStatus::Flagged;
+        let mut g = Game::from_layout("...\n...\n..*")
+            .unwrap()
+            .with_view("###\n###\n##F")
+            .unwrap();
         g.update(0, 0);
-        dbg!(g.board.board.clone());
-        let b = vec![
-            vec![DisplayStatus::Visible(0), DisplayStatus::Visible(0), DisplayStatus::Visible(0)],
-            vec![DisplayStatus::Visible(0), DisplayStatus::Visible(1), DisplayStatus::Visible(1)],
-            vec![DisplayStatus::Visible(0), DisplayStatus::Visible(1), DisplayStatus::Flagged]
-        ];
-        assert_eq!(g.board.board, b);
+        let (b, _) = layout::parse_view("000\n011\n01F").unwrap();
+        assert_eq!(g.board.board, b.board);
     }
     #[test]
     fn game_update_test_5() {
-        let c = GameConfig{width: 3, height: 3, mines: 1};
-        let mut a = Answer::new(3, 3);
-        a.board[0][0] = State::Mine;
-        a.board[0][1] = State::Empty(1);
-        a.board[1][0] = State::Empty(1);
-        a.board[1][1] = State::Empty(2);
-        a.board[1][2] = State::Empty(1);
-        a.board[2][1] = State::Empty(1);
-        a.board[2][2] = State::Mine;
-        let mut g = Game {
-            answer: a,
-            board: Board::new(&c),
-            config: c,
-            status: GameStatus::InProgress,
-        };
-        g.board.board[2][2] = DisplayStatus::Flagged;
+        let mut g = Game::from_layout("*..\n...\n..*")
+            .unwrap()
+            .with_view("###\n###\n##F")
+            .unwrap();
         g.update(0, 2);
-        let b = vec![
-            vec![DisplayStatus::Hidden, DisplayStatus::Visible(1), DisplayStatus::Visible(0)],
-            vec![DisplayStatus::Hidden, DisplayStatus::Visible(2), DisplayStatus::Visible(1)],
-            vec![DisplayStatus::Hidden, DisplayStatus::Hidden, DisplayStatus::Flagged]
-        ];
-        assert_eq!(g.board.board, b);
+        assert!(Game::from_layout("..").unwrap().with_view("#\n#").is_err());
+        assert_eq!(g.view(), "3 3 2\n#10\n#21\n##F\n");
+        assert_eq!(g.layout(), "3 3 2\n*..\n...\n..*\n");
     }
     #[test]
     fn game_test_1() {
-        let c = GameConfig{width: 3, height: 3, mines: 0};
+        let c = GameConfig::new(3, 3, 0);
         let mut g = Game::init(c).unwrap();
         let _a = g.try_update(&Input{x: 0, y: 0, act: Action::Click});
         assert_eq!(g.check_win(), true);
     }
     #[test]
     fn game_test_2() {
-        let c = GameConfig{width: 3, height: 3, mines: 0};
-        let mut g = Game::init(c).unwrap();
+        let c = GameConfig::new(3, 3, 0);
+        let mut g = Game::from_answer(Answer::new(3, 3), c);
         g.config.mines = 1;
         g.answer.board[0][0] = State::Mine;
         g.answer.board[0][1] = State::Empty(1);
@@ -567,6 +1451,454 @@ mod tests {
         dbg!(g.board.board.clone());
         assert_eq!(g.check_win(), true);
     }
+    #[test]
+    fn game_chord_test_1() {
+        let c = GameConfig::new(3, 3, 1);
+        let mut a = Answer::new(3, 3);
+        a.board[0][0] = State::Mine;
+        count_mine(&mut a, &*c.topology());
+        let mut g = Game::from_answer(a, c);
+        g.try_update(&Input{x: 1, y: 1, act: Action::Click}).unwrap();
+        assert_eq!(
+            g.try_update(&Input{x: 1, y: 1, act: Action::Chord}),
+            Err(MinesweeperError::FlagMismatch { x: 1, y: 1, number: 1, flags: 0 })
+        );
+        g.try_update(&Input{x: 0, y: 0, act: Action::RightClick}).unwrap();
+        g.try_update(&Input{x: 1, y: 1, act: Action::Chord}).unwrap();
+        assert_eq!(g.board.board[0][1], DisplayStatus::Visible(1));
+        assert_eq!(g.board.board[2][2], DisplayStatus::Visible(0));
+        assert_eq!(g.status, GameStatus::Won);
+    }
+    #[test]
+    fn game_chord_test_2() {
+        let c = GameConfig::new(3, 3, 1);
+        let mut a = Answer::new(3, 3);
+        a.board[0][0] = State::Mine;
+        count_mine(&mut a, &*c.topology());
+        let mut g = Game::from_answer(a, c);
+        g.try_update(&Input{x: 1, y: 1, act: Action::Click}).unwrap();
+        g.try_update(&Input{x: 2, y: 2, act: Action::RightClick}).unwrap();
+        g.try_update(&Input{x: 1, y: 1, act: Action::Chord}).unwrap();
+        assert_eq!(g.status, GameStatus::Lost);
+        assert_eq!(
+            g.try_update(&Input{x: 0, y: 0, act: Action::Chord}),
+            Err(MinesweeperError::GameOver)
+        );
+    }
+    #[test]
+    fn game_hint_test() {
+        let c = GameConfig::new(3, 3, 1);
+        let mut a = Answer::new(3, 3);
+        a.board[2][2] = State::Mine;
+        count_mine(&mut a, &*c.topology());
+        let mut g = Game::from_answer(a, c);
+        g.try_update(&Input{x: 0, y: 0, act: Action::Click}).unwrap();
+        g.try_update(&input("h\n".as_bytes()).unwrap()).unwrap();
+        let h = g.last_hint.clone().unwrap();
+        assert_eq!((h.x, h.y, h.mine), (2, 2, true));
+        assert_eq!(g.hints_used(), 1);
+        assert_eq!(
+            g.try_update(&Input{x: 2, y: 2, act: Action::RightClick}),
+            Err(MinesweeperError::GameOver)
+        );
+        assert_eq!(g.last_hint, None);
+        assert_eq!(g.hints_used(), 1);
+        // Before the first click the middle is safe, if there is one
+        let mut g = Game::init(GameConfig::new(3, 3, 1)).unwrap();
+        assert_eq!(g.hint().map(|h| (h.x, h.y)), Some((1, 1)));
+        let mut g = Game::placehold();
+        assert_eq!(
+            g.try_update(&Input{x: 0, y: 0, act: Action::Hint}),
+            Err(MinesweeperError::NoCertainMove)
+        );
+        assert_eq!(g.hints_used(), 0);
+    }
+    #[test]
+    fn game_efficiency_test() {
+        let mut g = Game::from_layout("..*..\n..*..\n*....\n").unwrap();
+        assert_eq!(g.metrics().unwrap().bbbv, 4);
+        for (x, y, act) in [
+            (0, 0, Action::Click),
+            (0, 0, Action::Click),
+            (2, 0, Action::RightClick),
+        ] {
+            let _ = g.try_update(&Input { x, y, act });
+        }
+        assert_eq!(g.efficiency(), None);
+        g.try_update(&Input{x: 0, y: 2, act: Action::Click}).unwrap();
+        let e = g.efficiency().unwrap();
+        assert_eq!((e.bbbv, e.solved), (4, 1));
+        assert_eq!(e.clicks, analysis::Clicks { left: 3, right: 1, chord: 0, useful: 3 });
+        assert_eq!(e.ioe, 0.25);
+        assert_eq!(e.throughput, 1.0 / 3.0);
+    }
+    #[test]
+    fn game_timer_test() {
+        let mut g = Game::from_layout("..*..\n..*..\n*....\n").unwrap();
+        g.try_update(&Input{x: 0, y: 2, act: Action::RightClick}).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert_eq!(g.elapsed(), 0);
+        g.try_update(&Input{x: 0, y: 0, act: Action::Click}).unwrap();
+        g.pause();
+        assert_eq!(
+            g.try_update(&Input{x: 0, y: 4, act: Action::Click}),
+            Err(MinesweeperError::Paused)
+        );
+        assert_eq!(g.undo(), Err(MinesweeperError::Paused));
+        let mut buffer = vec![];
+        g.show_str(&mut buffer);
+        assert!(buffer.iter().all(|p| p.is_empty()));
+        let held = g.elapsed();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        assert_eq!(g.elapsed(), held);
+        g.resume();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        g.try_update(&Input{x: 2, y: 0, act: Action::Click}).unwrap();
+        assert_eq!(g.status, GameStatus::Lost);
+        let stopped = g.elapsed();
+        assert!(stopped >= held + 10);
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        assert_eq!(g.elapsed(), stopped);
+    }
+    #[test]
+    fn game_question_test() {
+        let text = "..*..\n..*..\n*....\n";
+        let right = |g: &mut Game, x, y| g.try_update(&Input{x, y, act: Action::RightClick});
+        let mut g = Game::from_layout(text).unwrap();
+        right(&mut g, 0, 0).unwrap();
+        right(&mut g, 0, 0).unwrap();
+        assert_eq!(g.board.board[0][0], DisplayStatus::Hidden);
+        let mut g = Game::from_layout(text).unwrap();
+        g.config = g.config.with_questions(true);
+        right(&mut g, 0, 0).unwrap();
+        right(&mut g, 0, 0).unwrap();
+        assert_eq!(g.board.board[0][0], DisplayStatus::Questioned);
+        // A question mark is cleared by the flood fill and can be clicked
+        right(&mut g, 1, 0).unwrap();
+        right(&mut g, 1, 0).unwrap();
+        g.try_update(&Input{x: 0, y: 0, act: Action::Click}).unwrap();
+        assert_eq!(g.board.board[1][0], DisplayStatus::Visible(1));
+        right(&mut g, 0, 4).unwrap();
+        right(&mut g, 0, 4).unwrap();
+        let mut buffer = vec![];
+        g.show_str(&mut buffer);
+        assert_eq!(buffer[4], "?");
+        right(&mut g, 0, 4).unwrap();
+        assert_eq!(g.board.board[0][4], DisplayStatus::Hidden);
+        right(&mut g, 0, 4).unwrap();
+        right(&mut g, 0, 4).unwrap();
+        g.try_update(&Input{x: 0, y: 4, act: Action::Click}).unwrap();
+        assert_eq!(g.board.board[0][4], DisplayStatus::Visible(0));
+    }
+    #[test]
+    fn game_mines_remaining_test() {
+        let mut g = Game::from_layout("*..\n...\n").unwrap();
+        g.config = g.config.with_questions(true);
+        for (x, y) in [(0, 0), (0, 1), (1, 1)] {
+            g.try_update(&Input{x, y, act: Action::RightClick}).unwrap();
+        }
+        assert_eq!(g.mines_remaining(), -2);
+        // A question mark is not a flag
+        g.try_update(&Input{x: 1, y: 1, act: Action::RightClick}).unwrap();
+        assert_eq!(g.mines_remaining(), -1);
+        g.undo().unwrap();
+        assert_eq!(g.mines_remaining(), -2);
+        let mut buffer = vec![];
+        g.show(&mut buffer);
+        assert!(String::from_utf8(buffer).unwrap().ends_with("Mines left: -2\n"));
+        // Flagging every hidden plaque is the most flags there can be
+        for (x, y) in [(0, 2), (1, 0), (1, 2)] {
+            g.try_update(&Input{x, y, act: Action::RightClick}).unwrap();
+        }
+        assert_eq!(g.board.flags, 6);
+        assert_eq!(g.mines_remaining(), -5);
+        assert_eq!(
+            g.try_update(&Input{x: 0, y: 0, act: Action::Click}),
+            Err(MinesweeperError::Flagged { x: 0, y: 0 })
+        );
+        assert_eq!(g.board.flags, 6);
+    }
+    #[test]
+    fn game_loss_test() {
+        let mut g = Game::from_layout("*..\n..*\n").unwrap();
+        for (x, y, act) in [
+            (0, 0, Action::RightClick),
+            (1, 0, Action::RightClick),
+            (0, 1, Action::Click),
+        ] {
+            g.try_update(&Input { x, y, act }).unwrap();
+        }
+        assert_eq!(g.loss_view(), None);
+        g.try_update(&Input{x: 1, y: 2, act: Action::Click}).unwrap();
+        assert_eq!(g.status, GameStatus::Lost);
+        let mut buffer = vec![];
+        g.show_str(&mut buffer);
+        assert_eq!(buffer, vec!["&#128681", "2", "", "&#10060", "", "&#128165"]);
+        assert_eq!(g.loss_view(), Some(buffer));
+        let mut buffer = vec![];
+        g.show(&mut buffer);
+        assert!(String::from_utf8(buffer).unwrap().contains(" W  *  X "));
+        // A loss by chording shows the mine behind the wrong flag
+        let mut g = Game::from_layout("*..\n...\n").unwrap();
+        g.try_update(&Input{x: 1, y: 1, act: Action::Click}).unwrap();
+        g.try_update(&Input{x: 1, y: 0, act: Action::RightClick}).unwrap();
+        g.try_update(&Input{x: 1, y: 1, act: Action::Chord}).unwrap();
+        assert_eq!(g.loss_view().unwrap()[..4], ["&#128165", "1", "0", "&#10060"]);
+    }
+    #[test]
+    fn game_torus_test() {
+        let c = GameConfig::new(4, 3, 1).with_grid(topology::Grid::Torus);
+        let mut a = Answer::new(4, 3);
+        a.board[0][0] = State::Mine;
+        count_mine(&mut a, &*c.topology());
+        // Every other plaque of a board 3 high touches the mine on a torus
+        assert_eq!(a.board[2][3], State::Empty(1));
+        assert_eq!(a.board[1][2], State::Empty(0));
+        // The zeros of the middle column open the columns on both sides
+        let mut g = Game::from_answer(a, c);
+        g.try_update(&Input{x: 1, y: 2, act: Action::Click}).unwrap();
+        assert_eq!(g.board.total_count, 9);
+        assert_eq!(g.board.board[0][3], DisplayStatus::Visible(1));
+        // The first click keeps 9 plaques free anywhere on a torus
+        let c = GameConfig::new(4, 3, 4)
+            .with_grid(topology::Grid::Torus)
+            .with_first_click(FirstClick::Opening);
+        assert_eq!(
+            Answer::init(&c, &*c.topology()).err(),
+            Some(MinesweeperError::TooManyMines { requested: 4, capacity: 3 })
+        );
+    }
+    #[test]
+    fn game_hex_test() {
+        let c = GameConfig::new(3, 3, 1).with_grid(topology::Grid::Hex);
+        let mut a = Answer::new(3, 3);
+        a.board[0][1] = State::Mine;
+        count_mine(&mut a, &*c.topology());
+        // The odd row is pushed right, (1, 0) touches the mine and (1, 2) does not
+        assert_eq!(a.board[1][0], State::Empty(1));
+        assert_eq!(a.board[1][2], State::Empty(0));
+        let mut g = Game::from_answer(a, c);
+        g.try_update(&Input{x: 2, y: 2, act: Action::Click}).unwrap();
+        assert_eq!(g.board.total_count, 7);
+        let d = g.deduce();
+        assert_eq!((d.safe, d.mines), (vec![(0, 0)], vec![(0, 1)]));
+        g.try_update(&Input{x: 0, y: 0, act: Action::Click}).unwrap();
+        assert_eq!(g.status, GameStatus::Won);
+        let mut buffer = vec![];
+        g.show(&mut buffer);
+        let text = String::from_utf8(buffer).unwrap();
+        assert!(text.starts_with(" 1  *  1 \n   1  1  0 \n 0  0  0 \n"));
+    }
+    #[test]
+    fn game_triangle_test() {
+        let grid = topology::Grid::Triangle(topology::Neighborhood::Vertex);
+        let c = GameConfig::new(5, 2, 1).with_grid(grid);
+        let mut a = Answer::new(5, 2);
+        a.board[0][0] = State::Mine;
+        count_mine(&mut a, &*c.topology());
+        // Only the corner of (1, 2) touches the mine, and nothing reaches (0, 3)
+        assert_eq!(a.board[1][2], State::Empty(1));
+        assert_eq!(a.board[0][3], State::Empty(0));
+        let mut g = Game::from_answer(a, c);
+        g.try_update(&Input{x: 1, y: 4, act: Action::Click}).unwrap();
+        assert_eq!(g.board.total_count, 8);
+        g.try_update(&Input{x: 1, y: 0, act: Action::Click}).unwrap();
+        assert_eq!(g.status, GameStatus::Won);
+        let mut buffer = vec![];
+        g.show(&mut buffer);
+        let text = String::from_utf8(buffer).unwrap();
+        assert!(text.starts_with("/ *\\ 1/ 1\\ 0/ 0\\\n\\ 1/ 1\\ 1/ 0\\ 0/\n"));
+        // Across edges only, the corner of (1, 1) does not count
+        let grid = topology::Grid::Triangle(topology::Neighborhood::Edge);
+        let mut g = Game::from_layout("..*..\n.....\n").unwrap();
+        g.config.grid = grid;
+        g.reshape();
+        count_mine(&mut g.answer, &*g.topology);
+        assert_eq!(g.answer.board[1][1], State::Empty(0));
+        g.try_update(&Input{x: 1, y: 4, act: Action::Click}).unwrap();
+        assert_eq!(g.board.total_count, 5);
+    }
+    #[test]
+    fn game_cube_test() {
+        let c = GameConfig::new(3, 3, 1).with_grid(topology::Grid::Cube).with_depth(3);
+        // The first click keeps all 27 plaques free around the middle one
+        assert_eq!(
+            Answer::init(&c.with_first_click(FirstClick::Opening), &*c.topology()).err(),
+            Some(MinesweeperError::TooManyMines { requested: 1, capacity: 0 })
+        );
+        let mut a = Answer::new(3, 9);
+        a.board[0][0] = State::Mine;
+        count_mine(&mut a, &*c.topology());
+        // Row 4 is the middle of the middle layer, it touches every corner
+        assert_eq!(a.board[4][1], State::Empty(1));
+        assert_eq!(a.board[8][2], State::Empty(0));
+        // A view has the rows of every layer
+        assert!(Game::from_answer(a.clone(), c).with_view(&"###\n".repeat(9)).is_ok());
+        assert!(Game::from_answer(a.clone(), c).with_view(&"###\n".repeat(3)).is_err());
+        // A hint names its plaque as drawn, with the layer
+        let view = "#10\n110\n000\n110\n110\n000\n000\n000\n000\n";
+        let mut g = Game::from_answer(a.clone(), c).with_view(view).unwrap();
+        g.set_layer(2).unwrap();
+        g.try_update(&Input{x: 0, y: 0, act: Action::Hint}).unwrap();
+        let mut buffer = vec![];
+        g.show(&mut buffer);
+        let shown = String::from_utf8(buffer).unwrap();
+        assert!(shown.contains("Hint: (0, 0) on layer 1 is a mine, the 1 at ("), "{}", shown);
+        // A row past the layer drawn is off the board, not on the next layer
+        let mut g = Game::from_answer(a.clone(), c);
+        let typed = g.input("4 1 l\n".as_bytes()).unwrap().unwrap();
+        assert_eq!(
+            g.try_update(&typed),
+            Err(MinesweeperError::OutOfBounds { x: 10, y: 1 })
+        );
+        let mut g = Game::from_answer(a, c);
+        g.try_update(&Input{x: 8, y: 2, act: Action::Click}).unwrap();
+        assert_eq!(g.status, GameStatus::Won);
+        assert_eq!(g.turn_layer(-1), Err(MinesweeperError::NoLayer { layers: 3 }));
+        g.turn_layer(1).unwrap();
+        assert_eq!(g.layer(), 1);
+        assert_eq!(g.clicks().total(), 1);
+        // Drawing another layer is not a move
+        assert_eq!(g.replay().steps.len(), 1);
+        assert_eq!(g.from_viewport(2, 1), (5, 1));
+        assert_eq!(g.to_viewport(5, 1), (2, 1));
+        assert_eq!(g.layer_of(5), 1);
+        let mut buffer = vec![];
+        g.show_str(&mut buffer);
+        assert_eq!(buffer, vec!["1", "1", "0", "1", "1", "0", "0", "0", "0"]);
+        let mut buffer = vec![];
+        g.show(&mut buffer);
+        assert!(String::from_utf8(buffer).unwrap().contains("Layer 2 of 3"));
+        assert_eq!(g.input(">\n".as_bytes()), Ok(None));
+        assert_eq!(g.layer(), 2);
+        assert_eq!(g.turn_layer(1), Err(MinesweeperError::NoLayer { layers: 3 }));
+        // A typed move is on the layer drawn
+        let typed = g.input("0 0 r\n".as_bytes()).unwrap().unwrap();
+        assert_eq!((typed.x, typed.y), (6, 0));
+        // Only a cube has layers
+        assert_eq!(
+            Game::init(GameConfig::new(3, 3, 1).with_grid(topology::Grid::Hex).with_depth(3)).err(),
+            Some(MinesweeperError::LayersWithoutCube { depth: 3 })
+        );
+    }
+    #[test]
+    fn game_flood_test() {
+        // One opening of 90 000 plaques, too deep for a recursive fill
+        let mut text = format!("*{}\n", ".".repeat(299));
+        text.push_str(&format!("{}\n", ".".repeat(300)).repeat(299));
+        let mut g = Game::from_layout(&text).unwrap();
+        g.try_update(&Input{x: 299, y: 299, act: Action::Click}).unwrap();
+        assert_eq!(g.board.total_count, 300 * 300 - 1);
+        assert_eq!(g.status, GameStatus::Won);
+    }
+    #[test]
+    fn game_scroll_test() {
+        let mut g = Game::from_layout("*..\n...\n").unwrap();
+        assert_eq!(g.scroll(1, 0), Err(MinesweeperError::NoWrap));
+        g.config.grid = topology::Grid::Torus;
+        g.reshape();
+        g.try_update(&Input{x: 0, y: 0, act: Action::RightClick}).unwrap();
+        g.scroll(-1, 4).unwrap();
+        assert_eq!(g.viewport, (1, 1));
+        assert_eq!(g.from_viewport(1, 2), (0, 0));
+        assert_eq!(g.to_viewport(0, 0), (1, 2));
+        let mut buffer = vec![];
+        g.show_str(&mut buffer);
+        assert_eq!(buffer, vec!["", "", "", "", "", "&#128681"]);
+        let mut buffer = vec![];
+        g.show(&mut buffer);
+        assert!(String::from_utf8(buffer).unwrap().starts_with(" *  *  * \n *  *  F \n"));
+        // A move typed on the terminal is on the board as drawn
+        let typed = g.input("1 2 r\n".as_bytes()).unwrap().unwrap();
+        assert_eq!((typed.x, typed.y), (0, 0));
+        g.try_update(&typed).unwrap();
+        assert_eq!(g.board.board[0][0], DisplayStatus::Hidden);
+        assert_eq!(g.input("h\n".as_bytes()), Ok(Some(Input{x: 0, y: 0, act: Action::Hint})));
+    }
+    #[test]
+    fn game_undo_test_1() {
+        let c = GameConfig::new(3, 3, 1).with_practice(true);
+        let mut a = Answer::new(3, 3);
+        a.board[2][2] = State::Mine;
+        count_mine(&mut a, &*c.topology());
+        let mut g = Game::from_answer(a, c);
+        assert_eq!(g.undo(), Err(MinesweeperError::NothingToUndo));
+        g.try_update(&Input{x: 0, y: 0, act: Action::Click}).unwrap();
+        assert_eq!(g.status, GameStatus::Won);
+        assert_eq!(
+            g.try_update(&Input{x: 2, y: 2, act: Action::RightClick}),
+            Err(MinesweeperError::GameOver)
+        );
+        // The whole flood fill is one move
+        g.undo().unwrap();
+        assert_eq!(g.board.board, Board::new(&*c.topology()).board);
+        assert_eq!(g.board.total_count, 0);
+        g.redo().unwrap();
+        assert_eq!(g.board.total_count, 8);
+        assert_eq!(g.status, GameStatus::Won);
+        assert_eq!(g.redo(), Err(MinesweeperError::NothingToRedo));
+        g.undo().unwrap();
+        g.try_update(&Input{x: 2, y: 2, act: Action::RightClick}).unwrap();
+        assert_eq!(g.board.board[2][2], DisplayStatus::Flagged);
+        assert_eq!(g.redo(), Err(MinesweeperError::NothingToRedo));
+    }
+    #[test]
+    fn game_undo_test_2() {
+        let c = GameConfig::new(3, 3, 1);
+        let mut a = Answer::new(3, 3);
+        a.board[2][2] = State::Mine;
+        count_mine(&mut a, &*c.topology());
+        let mut g = Game::from_answer(a.clone(), c);
+        g.try_update(&Input{x: 2, y: 2, act: Action::Click}).unwrap();
+        assert_eq!(g.status, GameStatus::Lost);
+        assert_eq!(g.undo(), Err(MinesweeperError::UndoAfterEnd));
+        let mut g = Game::from_answer(a, c.with_practice(true));
+        g.try_update(&Input{x: 2, y: 2, act: Action::Click}).unwrap();
+        g.undo().unwrap();
+        assert_eq!(g.status, GameStatus::InProgress);
+    }
+    #[test]
+    fn game_undo_test_3() {
+        // A win is final too, it would be recorded again once replayed
+        let c = GameConfig::new(3, 3, 1);
+        let mut a = Answer::new(3, 3);
+        a.board[2][2] = State::Mine;
+        count_mine(&mut a, &*c.topology());
+        let mut g = Game::from_answer(a, c);
+        g.try_update(&Input{x: 0, y: 0, act: Action::Click}).unwrap();
+        assert_eq!(g.status, GameStatus::Won);
+        assert_eq!(g.undo(), Err(MinesweeperError::UndoAfterEnd));
+        assert_eq!(g.status, GameStatus::Won);
+        assert_eq!(g.board.total_count, 8);
+    }
+    #[test]
+    fn game_save_test() {
+        let path = std::env::temp_dir().join("ms_game_save_test.json");
+        let c = GameConfig::new(9, 9, 10).with_seed(7);
+        let mut g = Game::init(c).unwrap();
+        g.try_update(&Input{x: 4, y: 4, act: Action::Click}).unwrap();
+        let hidden = g.board.board
+            .iter()
+            .flatten()
+            .position(|&d| d == DisplayStatus::Hidden)
+            .unwrap();
+        g.try_update(&Input{x: hidden / 9, y: hidden % 9, act: Action::RightClick}).unwrap();
+        g.save(&path).unwrap();
+        let l = Game::load(&path).unwrap();
+        assert_eq!(l.answer.board, g.answer.board);
+        assert_eq!(l.board.board, g.board.board);
+        assert_eq!(l.board.total_count, g.board.total_count);
+        assert_eq!(l.status, g.status);
+        assert_eq!(l.seed(), 7);
+        assert!(l.mines_placed);
+        std::fs::write(&path, r#"{"version": 0, "game": {}}"#).unwrap();
+        assert_eq!(
+            Game::load(&path).err(),
+            Some(MinesweeperError::SaveVersion { found: 0, expected: SAVE_VERSION })
+        );
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(Game::load(&path), Err(MinesweeperError::Io { .. })));
+    }
     /// Test for input function with std::io::stdin().lock()
     /// A command line input
     #[test]
@@ -587,11 +1919,20 @@ mod tests {
         assert_eq!(j.x, 0);
         assert_eq!(j.y, 1);
         assert_eq!(j.act, Action::RightClick);
+        let k = input("1 2 c\n".as_bytes()).unwrap();
+        assert_eq!(k.act, Action::Chord);
     }
     #[test]
     fn input_test_3() {
         let k = input("0 -1 l\n".as_bytes());
-        unsafe {assert_eq!(k.unwrap_err_unchecked(), "Failed to parse y")};
+        unsafe {assert_eq!(k.unwrap_err_unchecked(), MinesweeperError::Parse { field: "y" })};
+    }
+    #[test]
+    fn input_axial_test() {
+        let i = input("a -1 3 r\n".as_bytes()).unwrap();
+        assert_eq!((i.x, i.y, i.act), (3, 0, Action::RightClick));
+        let k = input("a -2 3 l\n".as_bytes());
+        assert_eq!(k.err(), Some(MinesweeperError::Parse { field: "q" }));
     }
     #[test]
     fn config_init_test_1() {
@@ -617,7 +1958,7 @@ mod tests {
                 "10".to_string()
             ]
         );
-        assert_eq!(c.unwrap_err(), "Failed to parse height");
+        assert_eq!(c.unwrap_err(), MinesweeperError::Parse { field: "height" });
     }
     /// Test if show() function works
     /// 
@@ -626,11 +1967,12 @@ mod tests {
     #[test]
     #[ignore]
     fn show_test_1() {
-        let c = GameConfig{width: 3, height: 3, mines: 0};
+        let c = GameConfig::new(3, 3, 0);
         let mut g = Game::init(c).unwrap();
         let mut buffer = std::io::stdout();
         g.show(&mut buffer);
-        g.try_update(&input("0 1 l\n".as_bytes()).unwrap()).unwrap();
+        let typed = g.input("0 1 l\n".as_bytes()).unwrap().unwrap();
+        g.try_update(&typed).unwrap();
         g.show(&mut buffer);
         buffer.flush().unwrap();
         panic!("");
@@ -18,8 +18,9 @@ fn main() {
         tx: Mutex::new(api_tx)
     })
     .setup(|app| {
+        let data_dir = app.path_resolver().app_data_dir();
         tauri::async_runtime::spawn( async move {
-            cli::main_thread(api_rx, apo_tx).await;
+            cli::main_thread(api_rx, apo_tx, data_dir).await;
         });
         let app_handle = app.handle();
         tauri::async_runtime::spawn(async move {
@@ -33,7 +34,23 @@ fn main() {
     })
     .invoke_handler(tauri::generate_handler![
         cli::start_game,
-        cli::display
+        cli::win_rate,
+        cli::best_times,
+        cli::streaks,
+        cli::left_click,
+        cli::right_click,
+        cli::chord,
+        cli::axial_click,
+        cli::probability,
+        cli::hint,
+        cli::undo,
+        cli::redo,
+        cli::pause,
+        cli::resume,
+        cli::scroll,
+        cli::layer,
+        cli::save_game,
+        cli::load_game
     ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
@@ -0,0 +1,182 @@
+//! Replays
+//!
+//! Every move given to a `Game` is logged with the milliseconds since the
+//! first one. The log, with the configuration and the layout of the mines
+//! (or the seed when no mine is placed yet), is enough to play the whole
+//! game again and check that it ends the same way.
+
+use crate::{layout, versioned, Game, GameConfig, GameStatus, Input, MinesweeperError};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Version of the replay file format, see `versioned`
+const REPLAY_VERSION: u32 = 1;
+
+/// A move of the log
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum Event {
+    /// An input given to `Game::try_update`, accepted or not
+    Input(Input),
+    Undo,
+    Redo,
+}
+
+/// A move and when it was played
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct Step {
+    /// Milliseconds since the first move
+    pub time: u64,
+    pub event: Event,
+}
+
+/// What is written to disk by `Replay::save`
+#[derive(Serialize, Deserialize)]
+pub struct Replay {
+    version: u32,
+    config: GameConfig,
+    seed: u64,
+    /// The mines as a plain-text layout, `None` if none were placed
+    layout: Option<String>,
+    pub steps: Vec<Step>,
+    /// How the game ended, or `InProgress` if it did not
+    pub status: GameStatus,
+}
+
+impl Replay {
+    /// The replay of a game so far
+    pub fn new(game: &Game) -> Replay {
+        Replay {
+            version: REPLAY_VERSION,
+            config: game.config,
+            seed: game.seed,
+            layout: game.mines_placed.then(|| game.layout()),
+            steps: game.steps.clone(),
+            status: game.status,
+        }
+    }
+    /// A replay of moves played by another program, see `video`
+    pub(crate) fn imported(
+        config: GameConfig,
+        layout: String,
+        steps: Vec<Step>,
+        status: GameStatus,
+    ) -> Replay {
+        Replay {
+            version: REPLAY_VERSION,
+            config,
+            seed: 0,
+            layout: Some(layout),
+            steps,
+            status,
+        }
+    }
+    /// Milliseconds between the first and the last move
+    pub fn duration(&self) -> u64 {
+        self.steps.last().map_or(0, |s| s.time)
+    }
+    pub fn save(&self, path: &Path) -> Result<(), MinesweeperError> {
+        versioned::write(path, self)
+    }
+    /// Read a replay written by `save`
+    pub fn load(path: &Path) -> Result<Replay, MinesweeperError> {
+        versioned::read(path, REPLAY_VERSION)
+    }
+    /// The game before the first move
+    fn start(&self) -> Result<Game, MinesweeperError> {
+        match &self.layout {
+            Some(text) => {
+                let (answer, config) = layout::parse_answer(text)?;
+                // The layers of a cube are written one under the other
+                let (rows, columns) = self.config.topology().size();
+                if (config.width, config.height) != (columns, rows) {
+                    return Err(MinesweeperError::SaveFormat {
+                        message: "the layout does not match the configuration".to_string(),
+                    });
+                }
+                let mut game = Game::from_answer(answer, self.config);
+                game.seed = self.seed;
+                Ok(game)
+            },
+            None => Game::init(self.config.with_seed(self.seed)),
+        }
+    }
+    /// Play every move again, the game must end the way it was recorded
+    pub fn play(&self) -> Result<Game, MinesweeperError> {
+        let mut game = self.start()?;
+        for step in &self.steps {
+            // A move rejected when recorded is rejected again
+            let _ = match &step.event {
+                Event::Input(input) => game.try_update(input),
+                Event::Undo => game.undo(),
+                Event::Redo => game.redo(),
+            };
+        }
+        if game.status != self.status {
+            return Err(MinesweeperError::ReplayMismatch {
+                expected: self.status,
+                found: game.status,
+            });
+        }
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Action;
+
+    fn play(game: &mut Game, moves: &[(usize, usize, Action)]) {
+        for &(x, y, act) in moves {
+            let _ = game.try_update(&Input { x, y, act });
+        }
+    }
+
+    #[test]
+    fn replay_test_1() {
+        let mut game = Game::from_layout("*..\n...\n..*\n").unwrap();
+        play(&mut game, &[
+            (0, 0, Action::RightClick),
+            (0, 0, Action::Click),
+            (0, 2, Action::Click),
+        ]);
+        game.undo().unwrap();
+        game.redo().unwrap();
+        play(&mut game, &[(2, 0, Action::Click)]);
+        assert_eq!(game.status, GameStatus::Won);
+        let replay = Replay::new(&game);
+        assert_eq!(replay.steps.len(), 6);
+        assert_eq!(replay.steps[0].event, Event::Input(Input { x: 0, y: 0, act: Action::RightClick }));
+        assert_eq!(replay.steps[3].event, Event::Undo);
+        assert!(replay.steps.windows(2).all(|w| w[0].time <= w[1].time));
+        let text = serde_json::to_string(&replay).unwrap();
+        let replayed = versioned::parse::<Replay>(&text, REPLAY_VERSION).unwrap().play().unwrap();
+        assert_eq!(replayed.view(), game.view());
+    }
+    #[test]
+    fn replay_test_2() {
+        // Mines are placed on the first click, the seed is enough
+        let config = GameConfig::new(9, 9, 10).with_seed(7);
+        let mut game = Game::init(config).unwrap();
+        assert!(Replay::new(&game).layout.is_none());
+        play(&mut game, &[(4, 4, Action::Click)]);
+        let mut replay = Replay::new(&game);
+        replay.layout = None;
+        assert_eq!(replay.play().unwrap().view(), game.view());
+        replay.status = GameStatus::Won;
+        assert_eq!(
+            replay.play().err(),
+            Some(MinesweeperError::ReplayMismatch {
+                expected: GameStatus::Won,
+                found: GameStatus::InProgress,
+            })
+        );
+    }
+    #[test]
+    fn replay_version_test() {
+        assert_eq!(
+            versioned::parse::<Replay>(r#"{"version":0}"#, REPLAY_VERSION).err(),
+            Some(MinesweeperError::SaveVersion { found: 0, expected: REPLAY_VERSION })
+        );
+    }
+}
@@ -0,0 +1,942 @@
+//! Deductions from what the player can see
+//!
+//! The solver only reads the `DisplayStatus` grid of a `Board` and the
+//! total number of mines, never the `Answer`. Flags placed by the player
+//! are not trusted, a flagged plaque is as unknown as a hidden one.
+
+use crate::{Board, DisplayStatus, Topology};
+use crate::topology::Cell;
+
+/// Components larger than this are not enumerated by `solve`
+/// nor by `probabilities`
+const ENUMERATION_LIMIT: usize = 48;
+
+/// Plaques whose content is certain from the visible board
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct Deduction {
+    pub safe: Vec<(usize, usize)>,
+    pub mines: Vec<(usize, usize)>,
+}
+
+/// One plaque the player can settle and why
+#[derive(Debug, PartialEq, Eq, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Hint {
+    pub x: usize,
+    pub y: usize,
+    /// Whether the plaque is a mine, it is safe otherwise
+    pub mine: bool,
+    /// Human-readable explanation of the deduction
+    pub reason: String,
+}
+
+/// Plaques settled by one rule, with the explanation of that rule
+struct Step {
+    cells: Vec<Cell>,
+    mine: bool,
+    reason: String,
+}
+
+/// A visible number and the unknown plaques around it
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(crate) struct Constraint {
+    /// The visible number the constraint comes from
+    pub(crate) origin: Cell,
+    /// Unknown plaques around the number, sorted
+    pub(crate) cells: Vec<Cell>,
+    /// How many of `cells` are mines
+    pub(crate) mines: usize,
+}
+
+/// What has been deduced so far about each plaque
+/// `Some(true)` is a mine, `Some(false)` is safe
+pub(crate) type Known = Vec<Vec<Option<bool>>>;
+
+/// Every way the mines can lie in one connected part of the frontier,
+/// tallied by how many mines the part holds
+pub(crate) struct Component {
+    pub(crate) cells: Vec<Cell>,
+    /// `solutions[k]` counts the assignments with k mines
+    pub(crate) solutions: Vec<f64>,
+    /// `hits[k][i]` counts the assignments with k mines
+    /// where `cells[i]` is a mine
+    pub(crate) hits: Vec<Vec<f64>>,
+}
+
+/// Find every plaque that is certainly safe or certainly a mine
+pub(crate) fn solve(board: &Board, topology: &dyn Topology, mines: usize) -> Deduction {
+    let grid = &board.board;
+    let mut known: Known = grid
+        .iter()
+        .map(|line| vec![None; line.len()])
+        .collect();
+    loop {
+        let cons = constraints(grid, &known, topology);
+        let mut found = single(&cons);
+        if found.is_empty() {
+            found = subset(&cons);
+        }
+        if found.is_empty() {
+            found = enumerate(grid, &known, &cons, mines, topology);
+        }
+        if found.is_empty() {
+            break;
+        }
+        for ((x, y), mine) in found {
+            known[x][y] = Some(mine);
+        }
+    }
+    let mut deduction = Deduction::default();
+    for (x, line) in known.iter().enumerate() {
+        for (y, k) in line.iter().enumerate() {
+            match k {
+                Some(true) => deduction.mines.push((x, y)),
+                Some(false) => deduction.safe.push((x, y)),
+                None => (),
+            }
+        }
+    }
+    deduction
+}
+
+/// Find one plaque the player has not settled yet and explain it.
+/// Deductions that only confirm a flag are followed silently,
+/// the explanation is for the step that gives something new.
+/// Plaques are named in the reason by `place`, as the player sees them
+pub(crate) fn hint(
+    board: &Board,
+    topology: &dyn Topology,
+    mines: usize,
+    place: &dyn Fn(Cell) -> String,
+) -> Option<Hint> {
+    let grid = &board.board;
+    let mut known: Known = grid
+        .iter()
+        .map(|line| vec![None; line.len()])
+        .collect();
+    loop {
+        let cons = constraints(grid, &known, topology);
+        let mut steps = single_steps(grid, &cons, place);
+        if steps.is_empty() {
+            steps = subset_steps(grid, &cons, place);
+        }
+        if steps.is_empty() {
+            steps = enumerate(grid, &known, &cons, mines, topology)
+                .into_iter()
+                .map(|(cell, mine)| Step {
+                    cells: vec![cell],
+                    mine,
+                    reason: format!(
+                        "every layout that fits the numbers and the {} in total \
+                        has {} at {}",
+                        plural(mines, "mine", "mines"),
+                        if mine { "a mine" } else { "no mine" },
+                        place(cell)
+                    ),
+                })
+                .collect();
+        }
+        if steps.is_empty() {
+            return None;
+        }
+        for step in &steps {
+            // A mine already flagged is nothing new, a flag on a safe plaque is
+            let new = step.cells.iter().find(|&&(x, y)| {
+                !step.mine || grid[x][y] != DisplayStatus::Flagged
+            });
+            if let Some(&(x, y)) = new {
+                return Some(Hint {
+                    x, y,
+                    mine: step.mine,
+                    reason: step.reason.clone(),
+                });
+            }
+        }
+        for step in steps {
+            for (x, y) in step.cells {
+                known[x][y] = Some(step.mine);
+            }
+        }
+    }
+}
+
+fn plural(n: usize, one: &str, many: &str) -> String {
+    match n {
+        1 => format!("one {}", one),
+        2 => format!("two {}", many),
+        _ => format!("{} {}", n, many),
+    }
+}
+
+fn number_at(
+    grid: &[Vec<DisplayStatus>],
+    (x, y): Cell,
+    place: &dyn Fn(Cell) -> String,
+) -> String {
+    match grid[x][y] {
+        DisplayStatus::Visible(c) => format!("the {} at {}", c, place((x, y))),
+        _ => place((x, y)),
+    }
+}
+
+/// `single` with an explanation for every number
+fn single_steps(
+    grid: &[Vec<DisplayStatus>],
+    cons: &[Constraint],
+    place: &dyn Fn(Cell) -> String,
+) -> Vec<Step> {
+    let mut res = vec![];
+    for con in cons {
+        let n = match grid[con.origin.0][con.origin.1] {
+            DisplayStatus::Visible(c) => c as usize,
+            _ => continue,
+        };
+        let found = n - con.mines.min(n);
+        let count = con.cells.len();
+        let reason = if n == 0 {
+            format!(
+                "{} has no mine around it, so its hidden {} safe",
+                number_at(grid, con.origin, place),
+                if count == 1 { "cell is" } else { "cells are" }
+            )
+        } else if con.mines == 0 {
+            format!(
+                "{} already touches {}, so its other hidden {} safe",
+                number_at(grid, con.origin, place),
+                plural(found, "known mine", "known mines"),
+                if count == 1 { "cell is" } else { "cells are" }
+            )
+        } else if con.mines == count {
+            let all = match count {
+                1 => "it is a mine",
+                2 => "both are mines",
+                _ => "all of them are mines",
+            };
+            if found == 0 {
+                format!(
+                    "{} touches exactly {}, so {}",
+                    number_at(grid, con.origin, place),
+                    plural(count, "hidden cell", "hidden cells"),
+                    all
+                )
+            } else {
+                format!(
+                    "{} touches {} and exactly {} more, so {}",
+                    number_at(grid, con.origin, place),
+                    plural(found, "known mine", "known mines"),
+                    plural(count, "hidden cell", "hidden cells"),
+                    all
+                )
+            }
+        } else {
+            continue;
+        };
+        res.push(Step {
+            cells: con.cells.clone(),
+            mine: con.mines > 0,
+            reason,
+        });
+    }
+    res
+}
+
+/// `subset` with an explanation for every pair of numbers
+fn subset_steps(
+    grid: &[Vec<DisplayStatus>],
+    cons: &[Constraint],
+    place: &dyn Fn(Cell) -> String,
+) -> Vec<Step> {
+    let mut res = vec![];
+    for a in cons {
+        for b in cons {
+            if a.origin == b.origin
+                || a.cells.len() >= b.cells.len()
+                || !a.cells.iter().all(|c| b.cells.contains(c))
+                || b.mines < a.mines {
+                continue;
+            }
+            let rest: Vec<Cell> = b.cells
+                .iter()
+                .filter(|c| !a.cells.contains(c))
+                .cloned()
+                .collect();
+            let mines = b.mines - a.mines;
+            if mines != 0 && mines != rest.len() {
+                continue;
+            }
+            let (other, verb) = match rest.len() {
+                1 => ("other hidden cell".to_string(), "is"),
+                n => (format!("{} other hidden cells", n), "are"),
+            };
+            let what = match (mines, rest.len()) {
+                (0, _) => "safe",
+                (_, 1) => "a mine",
+                _ => "mines",
+            };
+            let short = match grid[b.origin.0][b.origin.1] {
+                DisplayStatus::Visible(c) => format!("the {}'s", c),
+                _ => "its".to_string(),
+            };
+            res.push(Step {
+                reason: format!(
+                    "every hidden cell around {} is also next to {}, \
+                    so {} {} {} {}",
+                    number_at(grid, a.origin, place),
+                    number_at(grid, b.origin, place),
+                    short, other, verb, what
+                ),
+                cells: rest,
+                mine: mines > 0,
+            });
+        }
+    }
+    res
+}
+
+/// The chance of a mine under every plaque that is not revealed yet,
+/// counted over every layout that agrees with the visible board.
+/// Revealed plaques and boards that contradict themselves give `None`,
+/// and so does every plaque when the frontier is too large to enumerate.
+pub(crate) fn probabilities(
+    board: &Board,
+    topology: &dyn Topology,
+    mines: usize,
+) -> Vec<Vec<Option<f64>>> {
+    let grid = &board.board;
+    let known: Known = grid
+        .iter()
+        .map(|line| vec![None; line.len()])
+        .collect();
+    let none: Vec<Vec<Option<f64>>> = grid
+        .iter()
+        .map(|line| vec![None; line.len()])
+        .collect();
+    let mut res = none.clone();
+    let cons = constraints(grid, &known, topology);
+    let comps = match components(&cons) {
+        Some(comps) => comps,
+        None => return none,
+    };
+    let inner = interior(grid, &known, &cons, topology);
+    let left = mines;
+    // The interior takes `left - t` mines when the frontier holds `t`,
+    // in `C(interior, left - t)` ways
+    let ways = interior_ways(inner.len(), left);
+    let weight = |t: usize| if t <= left { ways[left - t] } else { 0.0 };
+    for (i, comp) in comps.iter().enumerate() {
+        let others = comps
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .fold(vec![1.0], |acc, (_, c)| convolve(&acc, &c.solutions));
+        // Weight of the other parts and the interior for k mines in this one
+        let rest: Vec<f64> = (0..comp.solutions.len())
+            .map(|k| {
+                others
+                    .iter()
+                    .enumerate()
+                    .map(|(r, &n)| n * weight(k + r))
+                    .sum()
+            })
+            .collect();
+        let total: f64 = (0..comp.solutions.len())
+            .map(|k| comp.solutions[k] * rest[k])
+            .sum();
+        if total <= 0.0 {
+            return none;
+        }
+        for (c, &(x, y)) in comp.cells.iter().enumerate() {
+            let hit: f64 = (0..comp.solutions.len())
+                .map(|k| comp.hits[k][c] * rest[k])
+                .sum();
+            res[x][y] = Some(hit / total);
+        }
+    }
+    if !inner.is_empty() {
+        let all = comps
+            .iter()
+            .fold(vec![1.0], |acc, c| convolve(&acc, &c.solutions));
+        let (mut total, mut mines) = (0.0, 0.0);
+        for (t, &n) in all.iter().enumerate() {
+            if t <= left {
+                total += n * weight(t);
+                mines += n * weight(t) * (left - t) as f64;
+            }
+        }
+        if total <= 0.0 {
+            return none;
+        }
+        for &(x, y) in &inner {
+            res[x][y] = Some(mines / total / inner.len() as f64);
+        }
+    }
+    res
+}
+
+/// `C(n, r)` for every r up to `left`, scaled so that the largest is 1
+fn interior_ways(n: usize, left: usize) -> Vec<f64> {
+    // Work with logarithms, the counts overflow on large boards
+    let mut ln = vec![f64::NEG_INFINITY; left + 1];
+    let mut acc = 0.0;
+    for (r, l) in ln.iter_mut().enumerate().take(n + 1) {
+        if r > 0 {
+            acc += ((n - r + 1) as f64).ln() - (r as f64).ln();
+        }
+        *l = acc;
+    }
+    let top = ln.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    ln.iter().map(|l| (l - top).exp()).collect()
+}
+
+/// Distribution of the sum of two mine counts, scaled so that the largest is 1
+fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut res = vec![0.0; a.len() + b.len() - 1];
+    for (i, &x) in a.iter().enumerate() {
+        for (j, &y) in b.iter().enumerate() {
+            res[i + j] += x * y;
+        }
+    }
+    let top = res.iter().cloned().fold(0.0, f64::max);
+    if top > 0.0 {
+        res.iter_mut().for_each(|v| *v /= top);
+    }
+    res
+}
+
+/// Whether the player still has to find out what is under (x, y)
+pub(crate) fn is_unknown(
+    grid: &[Vec<DisplayStatus>],
+    known: &Known,
+    x: usize, y: usize
+) -> bool {
+    !matches!(grid[x][y], DisplayStatus::Visible(_)) && known[x][y].is_none()
+}
+
+/// One constraint for every visible number with unknown neighbors
+pub(crate) fn constraints(
+    grid: &[Vec<DisplayStatus>],
+    known: &Known,
+    topology: &dyn Topology,
+) -> Vec<Constraint> {
+    let mut res = vec![];
+    for (x, y) in topology.cells() {
+        let c = match grid[x][y] {
+            DisplayStatus::Visible(c) => c as usize,
+            _ => continue,
+        };
+        let mut cells = vec![];
+        let mut mines = 0;
+        for (nx, ny) in topology.neighbors(x, y) {
+            if is_unknown(grid, known, nx, ny) {
+                cells.push((nx, ny));
+            } else if known[nx][ny] == Some(true) {
+                mines += 1;
+            }
+        }
+        if cells.is_empty() {
+            continue;
+        }
+        cells.sort();
+        res.push(Constraint {
+            origin: (x, y),
+            cells,
+            mines: c.saturating_sub(mines),
+        });
+    }
+    res
+}
+
+/// A number whose unknown neighbors are all safe or all mines
+pub(crate) fn single(cons: &[Constraint]) -> Vec<(Cell, bool)> {
+    let mut res: Vec<(Cell, bool)> = vec![];
+    for con in cons {
+        if con.mines == 0 {
+            res.extend(con.cells.iter().map(|&c| (c, false)));
+        } else if con.mines == con.cells.len() {
+            res.extend(con.cells.iter().map(|&c| (c, true)));
+        }
+    }
+    res.sort();
+    res.dedup();
+    res
+}
+
+/// A number whose unknown neighbors contain those of another number,
+/// the plaques left over hold the difference of the two
+pub(crate) fn subset(cons: &[Constraint]) -> Vec<(Cell, bool)> {
+    let mut res: Vec<(Cell, bool)> = vec![];
+    for a in cons {
+        for b in cons {
+            if a.origin == b.origin
+                || a.cells.len() >= b.cells.len()
+                || !a.cells.iter().all(|c| b.cells.contains(c))
+                || b.mines < a.mines {
+                continue;
+            }
+            let rest: Vec<Cell> = b.cells
+                .iter()
+                .filter(|c| !a.cells.contains(c))
+                .cloned()
+                .collect();
+            let mines = b.mines - a.mines;
+            if mines == 0 {
+                res.extend(rest.iter().map(|&c| (c, false)));
+            } else if mines == rest.len() {
+                res.extend(rest.iter().map(|&c| (c, true)));
+            }
+        }
+    }
+    res.sort();
+    res.dedup();
+    res
+}
+
+/// Split the constrained plaques into groups that do not share a number
+/// and count every assignment of mines in each group,
+/// `None` if a group is larger than `ENUMERATION_LIMIT`
+pub(crate) fn components(cons: &[Constraint]) -> Option<Vec<Component>> {
+    // Group the constraints that share a plaque
+    let mut group: Vec<usize> = (0..cons.len()).collect();
+    fn root(group: &mut [usize], i: usize) -> usize {
+        let mut r = i;
+        while group[r] != r {
+            r = group[r];
+        }
+        group[i] = r;
+        r
+    }
+    for i in 0..cons.len() {
+        for j in i + 1..cons.len() {
+            if cons[i].cells.iter().any(|c| cons[j].cells.contains(c)) {
+                let (ri, rj) = (root(&mut group, i), root(&mut group, j));
+                group[ri] = rj;
+            }
+        }
+    }
+    let mut res = vec![];
+    for r in 0..cons.len() {
+        if root(&mut group, r) != r {
+            continue;
+        }
+        let members: Vec<&Constraint> = (0..cons.len())
+            .filter(|&i| root(&mut group, i) == r)
+            .map(|i| &cons[i])
+            .collect();
+        let mut cells: Vec<Cell> = members.iter().flat_map(|con| con.cells.clone()).collect();
+        cells.sort_unstable();
+        cells.dedup();
+        if cells.len() > ENUMERATION_LIMIT {
+            return None;
+        }
+        res.push(count_component(&members));
+    }
+    Some(res)
+}
+
+fn count_component(members: &[&Constraint]) -> Component {
+    // Plaques in the order the constraints meet them,
+    // so that each constraint is closed as early as possible
+    let mut cells: Vec<Cell> = vec![];
+    for con in members {
+        for c in &con.cells {
+            if !cells.contains(c) {
+                cells.push(*c);
+            }
+        }
+    }
+    let locals: Vec<Vec<usize>> = members
+        .iter()
+        .map(|con| {
+            con.cells
+                .iter()
+                .map(|c| cells.iter().position(|d| d == c).unwrap())
+                .collect()
+        })
+        .collect();
+    let mut watch: Vec<Vec<usize>> = vec![vec![]; cells.len()];
+    for (j, local) in locals.iter().enumerate() {
+        for &i in local {
+            watch[i].push(j);
+        }
+    }
+    let mut search = Search {
+        watch,
+        need: members.iter().map(|con| con.mines).collect(),
+        open: locals.iter().map(|local| local.len()).collect(),
+        assign: vec![false; cells.len()],
+        solutions: vec![0.0; cells.len() + 1],
+        hits: vec![vec![0.0; cells.len()]; cells.len() + 1],
+    };
+    search.run(0, 0);
+    Component {
+        cells,
+        solutions: search.solutions,
+        hits: search.hits,
+    }
+}
+
+/// Backtracking over the plaques of one component
+struct Search {
+    /// The constraints each plaque takes part in
+    watch: Vec<Vec<usize>>,
+    /// Mines each constraint still needs
+    need: Vec<usize>,
+    /// Plaques of each constraint not assigned yet
+    open: Vec<usize>,
+    assign: Vec<bool>,
+    solutions: Vec<f64>,
+    hits: Vec<Vec<f64>>,
+}
+
+impl Search {
+    fn run(&mut self, i: usize, mines: usize) {
+        if i == self.assign.len() {
+            self.solutions[mines] += 1.0;
+            for (j, &a) in self.assign.iter().enumerate() {
+                if a {
+                    self.hits[mines][j] += 1.0;
+                }
+            }
+            return;
+        }
+        for mine in [false, true] {
+            let fits = self.watch[i].iter().all(|&j| {
+                if mine {
+                    self.need[j] > 0
+                } else {
+                    self.open[j] > self.need[j]
+                }
+            });
+            if !fits {
+                continue;
+            }
+            for &j in &self.watch[i] {
+                self.open[j] -= 1;
+                if mine {
+                    self.need[j] -= 1;
+                }
+            }
+            self.assign[i] = mine;
+            self.run(i + 1, mines + mine as usize);
+            for &j in &self.watch[i] {
+                self.open[j] += 1;
+                if mine {
+                    self.need[j] += 1;
+                }
+            }
+        }
+        self.assign[i] = false;
+    }
+}
+
+/// Unknown plaques that no visible number touches
+pub(crate) fn interior(
+    grid: &[Vec<DisplayStatus>],
+    known: &Known,
+    cons: &[Constraint],
+    topology: &dyn Topology,
+) -> Vec<Cell> {
+    topology
+        .cells()
+        .into_iter()
+        .filter(|&(x, y)| {
+            is_unknown(grid, known, x, y)
+                && !cons.iter().any(|con| con.cells.contains(&(x, y)))
+        })
+        .collect()
+}
+
+/// Mines not deduced yet
+pub(crate) fn remaining(known: &Known, mines: usize) -> usize {
+    let found = known
+        .iter()
+        .flatten()
+        .filter(|&&k| k == Some(true))
+        .count();
+    mines.saturating_sub(found)
+}
+
+/// Which totals of mines a set of components can hold together
+fn reachable(parts: &[&Component]) -> Vec<bool> {
+    let mut reach = vec![true];
+    for part in parts {
+        let mut next = vec![false; reach.len() + part.solutions.len() - 1];
+        for (a, &ra) in reach.iter().enumerate() {
+            for (k, &n) in part.solutions.iter().enumerate() {
+                if ra && n > 0.0 {
+                    next[a + k] = true;
+                }
+            }
+        }
+        reach = next;
+    }
+    reach
+}
+
+/// Enumerate the whole frontier against the global mine count
+fn enumerate(
+    grid: &[Vec<DisplayStatus>],
+    known: &Known,
+    cons: &[Constraint],
+    mines: usize,
+    topology: &dyn Topology,
+) -> Vec<(Cell, bool)> {
+    let comps = match components(cons) {
+        Some(comps) => comps,
+        None => return vec![],
+    };
+    let inner = interior(grid, known, cons, topology);
+    let left = remaining(known, mines);
+    // A total of frontier mines is possible if the interior can take the rest
+    let fits = |total: usize| total <= left && left - total <= inner.len();
+    let mut res = vec![];
+    for (i, comp) in comps.iter().enumerate() {
+        let others: Vec<&Component> = comps
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .map(|(_, c)| c)
+            .collect();
+        let reach = reachable(&others);
+        let feasible: Vec<usize> = (0..comp.solutions.len())
+            .filter(|&k| comp.solutions[k] > 0.0)
+            .filter(|&k| reach.iter().enumerate().any(|(r, &ok)| ok && fits(k + r)))
+            .collect();
+        if feasible.is_empty() {
+            // The visible board contradicts itself
+            return vec![];
+        }
+        for (c, cell) in comp.cells.iter().enumerate() {
+            if feasible.iter().all(|&k| comp.hits[k][c] == 0.0) {
+                res.push((*cell, false));
+            } else if feasible.iter().all(|&k| comp.hits[k][c] == comp.solutions[k]) {
+                res.push((*cell, true));
+            }
+        }
+    }
+    if !inner.is_empty() {
+        let all: Vec<&Component> = comps.iter().collect();
+        let totals: Vec<usize> = reachable(&all)
+            .iter()
+            .enumerate()
+            .filter(|&(t, &ok)| ok && fits(t))
+            .map(|(t, _)| t)
+            .collect();
+        if !totals.is_empty() && totals.iter().all(|&t| t == left) {
+            res.extend(inner.iter().map(|&c| (c, false)));
+        } else if !totals.is_empty() && totals.iter().all(|&t| left - t == inner.len()) {
+            res.extend(inner.iter().map(|&c| (c, true)));
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::topology::Rectangle;
+
+    /// Build a board from rows of `#` hidden, `F` flagged and digits
+    fn board(rows: &[&str]) -> Board {
+        crate::layout::parse_view(&rows.join("\n")).unwrap().0
+    }
+
+    /// Plaques named by their row and column
+    fn at((x, y): Cell) -> String {
+        format!("({}, {})", x, y)
+    }
+
+    #[test]
+    fn solve_single_test() {
+        let b = board(&[
+            "#1",
+            "11",
+        ]);
+        let cons = constraints(&b.board, &vec![vec![None; 2]; 2], &Rectangle::new(2, 2));
+        assert_eq!(single(&cons), vec![((0, 0), true)]);
+        let d = solve(&b, &Rectangle::new(2, 2), 1);
+        assert_eq!(d.mines, vec![(0, 0)]);
+        assert!(d.safe.is_empty());
+    }
+    #[test]
+    fn solve_subset_test() {
+        // The 1 on the left touches two plaques, the 2 touches those and one more
+        let b = board(&[
+            "000",
+            "121",
+            "###",
+        ]);
+        let cons = constraints(&b.board, &vec![vec![None; 3]; 3], &Rectangle::new(3, 3));
+        assert!(single(&cons).is_empty());
+        assert_eq!(subset(&cons), vec![((2, 0), true), ((2, 2), true)]);
+        let d = solve(&b, &Rectangle::new(3, 3), 2);
+        assert_eq!(d.mines, vec![(2, 0), (2, 2)]);
+        assert_eq!(d.safe, vec![(2, 1)]);
+        let b = board(&[
+            "01#",
+            "01#",
+            "01#",
+        ]);
+        let d = solve(&b, &Rectangle::new(3, 3), 1);
+        assert_eq!(d.mines, vec![(1, 2)]);
+        assert_eq!(d.safe, vec![(0, 2), (2, 2)]);
+    }
+    #[test]
+    fn solve_enumeration_test() {
+        // The 1 cannot tell which of its three plaques is the mine,
+        // but the global count settles the two plaques it does not touch
+        let b = board(&[
+            "1##",
+            "###",
+        ]);
+        let cons = constraints(&b.board, &vec![vec![None; 3]; 2], &Rectangle::new(3, 2));
+        assert!(single(&cons).is_empty());
+        assert!(subset(&cons).is_empty());
+        let d = solve(&b, &Rectangle::new(3, 2), 3);
+        assert_eq!(d.mines, vec![(0, 2), (1, 2)]);
+        assert!(d.safe.is_empty());
+        let d = solve(&b, &Rectangle::new(3, 2), 1);
+        assert!(d.mines.is_empty());
+        assert_eq!(d.safe, vec![(0, 2), (1, 2)]);
+        // With a single mine the two 1s must share it
+        let b = board(&[
+            "1#1",
+            "###",
+        ]);
+        let d = solve(&b, &Rectangle::new(3, 2), 1);
+        assert!(d.mines.is_empty());
+        assert_eq!(d.safe, vec![(1, 0), (1, 2)]);
+    }
+    #[test]
+    fn probability_test_1() {
+        let b = board(&[
+            "1##",
+            "###",
+        ]);
+        let p = probabilities(&b, &Rectangle::new(3, 2), 1);
+        assert_eq!(p[0][0], None);
+        assert!((p[0][1].unwrap() - 1.0 / 3.0).abs() < 1e-9);
+        assert!((p[1][1].unwrap() - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(p[0][2], Some(0.0));
+        let p = probabilities(&b, &Rectangle::new(3, 2), 2);
+        assert!((p[1][0].unwrap() - 1.0 / 3.0).abs() < 1e-9);
+        assert!((p[1][2].unwrap() - 0.5).abs() < 1e-9);
+    }
+    #[test]
+    fn probability_test_2() {
+        // The 1s share one mine in the middle (2 ways, 3 ways to place
+        // the other mine below) or take one each at the sides (1 way)
+        let b = board(&[
+            "1#1",
+            "###",
+            "###",
+        ]);
+        let p = probabilities(&b, &Rectangle::new(3, 3), 2);
+        let close = |v: Option<f64>, e: f64| (v.unwrap() - e).abs() < 1e-9;
+        assert!(close(p[0][1], 3.0 / 7.0));
+        assert!(close(p[1][1], 3.0 / 7.0));
+        assert!(close(p[1][0], 1.0 / 7.0));
+        assert!(close(p[1][2], 1.0 / 7.0));
+        assert!(close(p[2][0], 2.0 / 7.0));
+        assert!(close(p[2][2], 2.0 / 7.0));
+        let sum: f64 = p.iter().flatten().flatten().sum();
+        assert!((sum - 2.0).abs() < 1e-9);
+    }
+    #[test]
+    fn probability_test_3() {
+        // A 1 next to nothing but revealed plaques cannot be right
+        let b = board(&[
+            "10",
+            "##",
+        ]);
+        let p = probabilities(&b, &Rectangle::new(2, 2), 5);
+        assert!(p.iter().flatten().all(|v| v.is_none()));
+    }
+    #[test]
+    fn probability_test_4() {
+        // One frontier of 60 plaques is past the enumeration limit
+        let top = "1".repeat(60);
+        let bottom = "#".repeat(60);
+        let b = board(&[&top, &bottom]);
+        let p = probabilities(&b, &Rectangle::new(60, 2), 20);
+        assert!(p.iter().flatten().all(|v| v.is_none()));
+    }
+    #[test]
+    fn hint_test_1() {
+        let b = board(&[
+            "00",
+            "22",
+            "##",
+        ]);
+        let h = hint(&b, &Rectangle::new(2, 3), 2, &at).unwrap();
+        assert_eq!((h.x, h.y, h.mine), (2, 0, true));
+        assert_eq!(
+            h.reason,
+            "the 2 at (1, 0) touches exactly two hidden cells, so both are mines"
+        );
+    }
+    #[test]
+    fn hint_test_2() {
+        // The mine next to the lower 1 is flagged already,
+        // the hint goes on to the plaques it makes safe
+        let b = board(&[
+            "01F#",
+            "011#",
+        ]);
+        let h = hint(&b, &Rectangle::new(4, 2), 1, &at).unwrap();
+        assert_eq!((h.x, h.y, h.mine), (0, 3, false));
+        assert_eq!(
+            h.reason,
+            "the 1 at (1, 2) already touches one known mine, \
+            so its other hidden cells are safe"
+        );
+    }
+    #[test]
+    fn hint_test_3() {
+        let b = board(&[
+            "000",
+            "121",
+            "###",
+        ]);
+        let h = hint(&b, &Rectangle::new(3, 3), 2, &at).unwrap();
+        assert_eq!((h.x, h.y, h.mine), (2, 2, true));
+        assert_eq!(
+            h.reason,
+            "every hidden cell around the 1 at (1, 0) is also next to the 2 at (1, 1), \
+            so the 2's other hidden cell is a mine"
+        );
+        let b = board(&[
+            "1##",
+            "###",
+        ]);
+        let h = hint(&b, &Rectangle::new(3, 2), 1, &at).unwrap();
+        assert_eq!((h.x, h.y, h.mine), (0, 2, false));
+        assert_eq!(
+            h.reason,
+            "every layout that fits the numbers and the one mine in total has no mine at (0, 2)"
+        );
+        assert_eq!(hint(&b, &Rectangle::new(3, 2), 2, &at), None);
+    }
+    #[test]
+    fn solve_flag_test() {
+        // Flags are not trusted
+        let b = board(&[
+            "1F",
+            "##",
+        ]);
+        let d = solve(&b, &Rectangle::new(2, 2), 1);
+        assert!(d.mines.is_empty());
+        assert!(d.safe.is_empty());
+    }
+    #[test]
+    fn solve_global_count_test() {
+        // Nothing is revealed and there are no mines at all
+        let b = board(&[
+            "##",
+            "##",
+        ]);
+        let d = solve(&b, &Rectangle::new(2, 2), 0);
+        assert_eq!(d.safe.len(), 4);
+        let d = solve(&b, &Rectangle::new(2, 2), 4);
+        assert_eq!(d.mines.len(), 4);
+    }
+}
@@ -0,0 +1,226 @@
+//! Statistics of finished games
+//!
+//! Every won or lost game is added to a store kept as one JSON file,
+//! records are in the order the games ended.
+
+use crate::{analysis, versioned, Game, GameConfig, GameStatus, MinesweeperError};
+use crate::topology::Grid;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Version of the statistics file format, see `versioned`
+const STATS_VERSION: u32 = 1;
+
+/// Board size, mine count and shape, the standard levels are
+/// three rectangles that may need a guess
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Serialize, Deserialize)]
+pub struct Level {
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    #[serde(default)]
+    pub grid: Grid,
+    /// Layers of a cube board, 1 for the other grids
+    #[serde(default = "crate::one_layer")]
+    pub depth: usize,
+    #[serde(default)]
+    pub no_guess: bool,
+}
+
+/// One finished game
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub level: Level,
+    /// Milliseconds on the game clock
+    pub time: u64,
+    /// Clicks, right clicks and chords, rejected ones included
+    pub clicks: usize,
+    pub bbbv: usize,
+    pub seed: u64,
+    pub result: GameStatus,
+    #[serde(default)]
+    pub hints_used: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub struct BestTime {
+    pub level: Level,
+    pub time: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize)]
+pub struct Streaks {
+    /// Games of the same result at the end of the store
+    pub current: usize,
+    /// Whether the current streak is of wins
+    pub winning: bool,
+    pub longest_win: usize,
+    pub longest_loss: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Stats {
+    version: u32,
+    pub records: Vec<Record>,
+}
+
+impl Default for Stats {
+    fn default() -> Stats {
+        Stats { version: STATS_VERSION, records: vec![] }
+    }
+}
+
+impl Level {
+    /// The level a game of this configuration is played at
+    pub fn new(config: &GameConfig) -> Level {
+        Level {
+            width: config.width,
+            height: config.height,
+            mines: config.mines,
+            grid: config.grid,
+            depth: config.layers(),
+            no_guess: config.no_guess,
+        }
+    }
+}
+
+impl Record {
+    /// The record of a game, `None` while it is in progress
+    pub fn new(game: &Game) -> Option<Record> {
+        if game.status == GameStatus::InProgress {
+            return None;
+        }
+        Some(Record {
+            level: Level::new(&game.config),
+            time: game.elapsed(),
+            clicks: game.clicks.total(),
+            bbbv: analysis::metrics(&game.answer, &*game.topology).bbbv,
+            seed: game.seed,
+            result: game.status,
+            hints_used: game.hints_used(),
+        })
+    }
+}
+
+impl Stats {
+    /// Read the store at `path`, a missing file is an empty store
+    pub fn load(path: &Path) -> Result<Stats, MinesweeperError> {
+        if !path.exists() {
+            return Ok(Stats::default());
+        }
+        versioned::read(path, STATS_VERSION)
+    }
+    pub fn save(&self, path: &Path) -> Result<(), MinesweeperError> {
+        versioned::write(path, self)
+    }
+    /// Add a game to the store at `path`
+    pub fn record(path: &Path, record: Record) -> Result<(), MinesweeperError> {
+        let mut stats = Stats::load(path)?;
+        stats.records.push(record);
+        stats.save(path)
+    }
+    /// Share of the games won, on one level or on all of them,
+    /// `None` without any game
+    pub fn win_rate(&self, level: Option<Level>) -> Option<f64> {
+        let games: Vec<&Record> = self.records
+            .iter()
+            .filter(|r| level.is_none() || level == Some(r.level))
+            .collect();
+        let won = games.iter().filter(|r| r.result == GameStatus::Won).count();
+        (!games.is_empty()).then(|| won as f64 / games.len() as f64)
+    }
+    /// Fastest win of every level played, in the order first won,
+    /// a win with hints is not a best time
+    pub fn best_times(&self) -> Vec<BestTime> {
+        let mut best: Vec<BestTime> = vec![];
+        for r in self.records.iter().filter(|r| r.result == GameStatus::Won && r.hints_used == 0) {
+            match best.iter_mut().find(|b| b.level == r.level) {
+                Some(b) => b.time = b.time.min(r.time),
+                None => best.push(BestTime { level: r.level, time: r.time }),
+            }
+        }
+        best
+    }
+    pub fn streaks(&self) -> Streaks {
+        let mut streaks = Streaks::default();
+        for r in &self.records {
+            let won = r.result == GameStatus::Won;
+            if streaks.current > 0 && streaks.winning == won {
+                streaks.current += 1;
+            } else {
+                streaks.current = 1;
+                streaks.winning = won;
+            }
+            let longest = if won { &mut streaks.longest_win } else { &mut streaks.longest_loss };
+            *longest = (*longest).max(streaks.current);
+        }
+        streaks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Action, Input};
+
+    fn level(width: usize) -> Level {
+        Level::new(&GameConfig::new(width, 8, 10))
+    }
+    fn record(width: usize, time: u64, won: bool) -> Record {
+        Record {
+            level: level(width),
+            time,
+            clicks: 0,
+            bbbv: 0,
+            seed: 0,
+            result: if won { GameStatus::Won } else { GameStatus::Lost },
+            hints_used: 0,
+        }
+    }
+
+    #[test]
+    fn stats_record_test() {
+        let mut game = Game::from_layout("*..\n...\n").unwrap();
+        assert_eq!(Record::new(&game), None);
+        game.try_update(&Input { x: 0, y: 0, act: Action::RightClick }).unwrap();
+        game.try_update(&Input { x: 1, y: 0, act: Action::Click }).unwrap();
+        game.try_update(&Input { x: 0, y: 0, act: Action::Hint }).unwrap();
+        game.try_update(&Input { x: 1, y: 2, act: Action::Click }).unwrap();
+        let r = Record::new(&game).unwrap();
+        assert_eq!(r.level, Level::new(&GameConfig::new(3, 2, 1)));
+        assert_eq!((r.clicks, r.bbbv, r.result), (3, 2, GameStatus::Won));
+        assert_eq!(r.hints_used, 1);
+    }
+    #[test]
+    fn stats_query_test() {
+        let mut stats = Stats::default();
+        assert_eq!(stats.win_rate(None), None);
+        for (width, time, won) in [(8, 30, true), (8, 20, true), (9, 50, false), (8, 25, true)] {
+            stats.records.push(record(width, time, won));
+        }
+        assert_eq!(stats.win_rate(None), Some(0.75));
+        assert_eq!(stats.win_rate(Some(level(8))), Some(1.0));
+        // Another shape or a no-guess board is another level
+        let torus = Level::new(&GameConfig::new(8, 8, 10).with_grid(Grid::Torus));
+        assert_eq!(stats.win_rate(Some(torus)), None);
+        let no_guess = Level::new(&GameConfig::new(8, 8, 10).with_no_guess(true));
+        assert_ne!(no_guess, level(8));
+        assert_eq!(stats.best_times(), vec![BestTime { level: level(8), time: 20 }]);
+        assert_eq!(
+            stats.streaks(),
+            Streaks { current: 1, winning: true, longest_win: 2, longest_loss: 1 }
+        );
+        stats.records.push(Record { hints_used: 1, ..record(8, 5, true) });
+        assert_eq!(stats.best_times(), vec![BestTime { level: level(8), time: 20 }]);
+    }
+    #[test]
+    fn stats_file_test() {
+        let path = std::env::temp_dir().join(format!("ms-stats-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        assert!(Stats::load(&path).unwrap().records.is_empty());
+        Stats::record(&path, record(8, 30, true)).unwrap();
+        Stats::record(&path, record(8, 40, false)).unwrap();
+        assert_eq!(Stats::load(&path).unwrap().records[1], record(8, 40, false));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,133 @@
+//! The game clock
+//!
+//! A monotonic clock started by the first reveal and stopped by the end
+//! of the game. While held, e.g. when the game is paused, it does not
+//! count. A saved clock is read back held.
+
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+enum State {
+    #[default]
+    Idle,
+    Running,
+    Stopped,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(into = "Saved", from = "Saved")]
+pub struct Timer {
+    /// Milliseconds counted before `since`
+    elapsed: u64,
+    /// When the clock last went on, `None` while it is held
+    since: Option<Instant>,
+    state: State,
+}
+
+/// What is written of a clock, the milliseconds counted so far
+#[derive(Serialize, Deserialize)]
+struct Saved {
+    elapsed: u64,
+    state: State,
+}
+
+impl From<Timer> for Saved {
+    fn from(timer: Timer) -> Saved {
+        Saved { elapsed: timer.elapsed(), state: timer.state }
+    }
+}
+
+impl From<Saved> for Timer {
+    fn from(saved: Saved) -> Timer {
+        Timer { elapsed: saved.elapsed, since: None, state: saved.state }
+    }
+}
+
+impl Timer {
+    /// Start an idle clock, a started one is left alone
+    pub fn start(&mut self) {
+        if self.state == State::Idle {
+            self.state = State::Running;
+            self.since = Some(Instant::now());
+        }
+    }
+    pub fn stop(&mut self) {
+        self.hold();
+        if self.state == State::Running {
+            self.state = State::Stopped;
+        }
+    }
+    /// Start a stopped clock again, e.g. when a loss is taken back
+    pub fn reopen(&mut self) {
+        if self.state == State::Stopped {
+            self.state = State::Running;
+            self.since = Some(Instant::now());
+        }
+    }
+    /// Stop counting without stopping the clock
+    pub fn hold(&mut self) {
+        self.elapsed = self.elapsed();
+        self.since = None;
+    }
+    /// Count again after `hold`
+    pub fn release(&mut self) {
+        if self.state == State::Running && self.since.is_none() {
+            self.since = Some(Instant::now());
+        }
+    }
+    /// Whether the clock was started and not stopped, held or not
+    pub fn running(&self) -> bool {
+        self.state == State::Running
+    }
+    /// Milliseconds counted so far
+    pub fn elapsed(&self) -> u64 {
+        self.elapsed + self.since.map_or(0, |s| s.elapsed().as_millis() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    #[test]
+    fn timer_test() {
+        let mut t = Timer::default();
+        sleep(Duration::from_millis(5));
+        assert_eq!(t.elapsed(), 0);
+        t.start();
+        sleep(Duration::from_millis(20));
+        t.hold();
+        let held = t.elapsed();
+        assert!(held >= 20);
+        sleep(Duration::from_millis(10));
+        assert_eq!(t.elapsed(), held);
+        t.release();
+        t.stop();
+        assert!(!t.running());
+        let stopped = t.elapsed();
+        sleep(Duration::from_millis(10));
+        assert_eq!(t.elapsed(), stopped);
+        t.start();
+        assert!(!t.running());
+        t.reopen();
+        assert!(t.running());
+    }
+    #[test]
+    fn timer_load_test() {
+        let mut t = Timer::default();
+        t.start();
+        sleep(Duration::from_millis(10));
+        let text = serde_json::to_string(&t).unwrap();
+        let mut loaded: Timer = serde_json::from_str(&text).unwrap();
+        let counted = loaded.elapsed();
+        assert!(counted >= 10 && loaded.running());
+        sleep(Duration::from_millis(10));
+        assert_eq!(loaded.elapsed(), counted);
+        loaded.release();
+        sleep(Duration::from_millis(10));
+        assert!(loaded.elapsed() >= counted + 10);
+    }
+}
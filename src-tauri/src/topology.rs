@@ -0,0 +1,388 @@
+//! The shape of the board
+//!
+//! Whatever the shape, plaques are stored in rows and columns and a `Cell`
+//! is the row and column of a plaque. A `Topology` says which cells are
+//! plaques of the board and which plaques are next to each other. The
+//! engine only goes through it to count the mines, flood the zeros and
+//! check the bounds of a move.
+
+use serde::{Deserialize, Serialize};
+
+/// The row and column of a plaque
+pub type Cell = (usize, usize);
+
+/// The shapes a game can be played on
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Hash, Serialize, Deserialize)]
+pub enum Grid {
+    #[default]
+    Rectangle,
+    /// A rectangle whose opposite edges are joined, see `Torus`
+    Torus,
+    /// Hexagons, see `Hex`
+    Hex,
+    /// Triangles, see `Triangle`
+    Triangle(Neighborhood),
+    /// Cubes in layers of rectangles, see `Cube`
+    Cube,
+}
+
+/// Which triangles are next to each other
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Hash, Serialize, Deserialize)]
+pub enum Neighborhood {
+    /// Only the triangles across an edge, 3 of them
+    Edge,
+    /// Every triangle a corner touches, 12 of them
+    #[default]
+    Vertex,
+}
+
+pub trait Topology: Send + Sync {
+    /// Rows and columns of the grid the plaques are stored in
+    fn size(&self) -> (usize, usize);
+    /// Whether (x, y) is a plaque of the board
+    fn contains(&self, x: usize, y: usize) -> bool;
+    /// The plaques next to (x, y)
+    fn neighbors(&self, x: usize, y: usize) -> Vec<Cell>;
+    /// Every plaque of the board, row by row
+    fn cells(&self) -> Vec<Cell> {
+        let (rows, columns) = self.size();
+        (0..rows)
+            .flat_map(|x| (0..columns).map(move |y| (x, y)))
+            .filter(|&(x, y)| self.contains(x, y))
+            .collect()
+    }
+    /// The most neighbors a plaque has
+    fn degree(&self) -> usize {
+        self.cells()
+            .iter()
+            .map(|&(x, y)| self.neighbors(x, y).len())
+            .max()
+            .unwrap_or(0)
+    }
+    /// Spaces before row x when drawn in the terminal
+    fn indent(&self, _x: usize) -> usize {
+        0
+    }
+}
+
+/// The classic board, up to 8 neighbors and fewer on the edges
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Rectangle {
+    width: usize,
+    height: usize,
+}
+
+impl Rectangle {
+    pub fn new(width: usize, height: usize) -> Rectangle {
+        Rectangle { width, height }
+    }
+}
+
+impl Topology for Rectangle {
+    fn size(&self) -> (usize, usize) {
+        (self.height, self.width)
+    }
+    fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.height && y < self.width
+    }
+    fn neighbors(&self, x: usize, y: usize) -> Vec<Cell> {
+        let steps: [(isize, isize); 8] = [
+            (0, 1), (0, -1), (1, 0), (-1, 0),
+            (1, 1), (1, -1), (-1, 1), (-1, -1)
+        ];
+        steps
+            .iter()
+            .filter_map(|&(dx, dy)| {
+                let nx = x.checked_add_signed(dx)?;
+                let ny = y.checked_add_signed(dy)?;
+                self.contains(nx, ny).then_some((nx, ny))
+            })
+            .collect()
+    }
+}
+
+/// A rectangle whose left edge is next to its right edge and whose top
+/// edge is next to its bottom edge, every plaque has 8 neighbors
+/// unless the board is less than 3 plaques across
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Torus {
+    width: usize,
+    height: usize,
+}
+
+impl Torus {
+    pub fn new(width: usize, height: usize) -> Torus {
+        Torus { width, height }
+    }
+}
+
+impl Topology for Torus {
+    fn size(&self) -> (usize, usize) {
+        (self.height, self.width)
+    }
+    fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.height && y < self.width
+    }
+    fn neighbors(&self, x: usize, y: usize) -> Vec<Cell> {
+        let mut res = vec![];
+        for (dx, dy) in [
+            (0, 1), (0, -1), (1, 0), (-1, 0),
+            (1, 1), (1, -1), (-1, 1), (-1, -1)
+        ] {
+            let nx = (x as isize + dx).rem_euclid(self.height as isize) as usize;
+            let ny = (y as isize + dy).rem_euclid(self.width as isize) as usize;
+            // On a narrow board two steps can land on the same plaque
+            if (nx, ny) != (x, y) && !res.contains(&(nx, ny)) {
+                res.push((nx, ny));
+            }
+        }
+        res
+    }
+}
+
+/// Hexagons with a point up, in rows where every odd row is pushed half
+/// a plaque to the right, so that a plaque has 6 neighbors: two on its
+/// row and two on each row around it. The row and column are the offset
+/// coordinates of the plaque, `from_axial` and `to_axial` convert them to
+/// the axial coordinates (q, r) where r is the row.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Hex {
+    width: usize,
+    height: usize,
+}
+
+impl Hex {
+    pub fn new(width: usize, height: usize) -> Hex {
+        Hex { width, height }
+    }
+    /// The row and column of the plaque at axial (q, r),
+    /// `None` left of the first column
+    pub fn from_axial(q: isize, r: usize) -> Option<Cell> {
+        let y = q + (r / 2) as isize;
+        (y >= 0).then_some((r, y as usize))
+    }
+    pub fn to_axial(x: usize, y: usize) -> (isize, usize) {
+        (y as isize - (x / 2) as isize, x)
+    }
+}
+
+impl Topology for Hex {
+    fn size(&self) -> (usize, usize) {
+        (self.height, self.width)
+    }
+    fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.height && y < self.width
+    }
+    fn neighbors(&self, x: usize, y: usize) -> Vec<Cell> {
+        // The rows around an odd row reach one column further right
+        let steps: [(isize, isize); 6] = if x % 2 == 1 {
+            [(0, 1), (0, -1), (1, 1), (1, 0), (-1, 1), (-1, 0)]
+        } else {
+            [(0, 1), (0, -1), (1, 0), (1, -1), (-1, 0), (-1, -1)]
+        };
+        steps
+            .iter()
+            .filter_map(|&(dx, dy)| {
+                let nx = x.checked_add_signed(dx)?;
+                let ny = y.checked_add_signed(dy)?;
+                self.contains(nx, ny).then_some((nx, ny))
+            })
+            .collect()
+    }
+    fn indent(&self, x: usize) -> usize {
+        if x % 2 == 1 { 2 } else { 0 }
+    }
+}
+
+/// Triangles in rows, pointing up and down in turn. The triangle at the
+/// start of the first row points up. A triangle shares its edges with the
+/// two beside it and the one under its flat side, and with the corners
+/// it touches 9 more triangles: 2 more on its row and 7 on the rows
+/// above and below, 5 on the side of its flat edge.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Triangle {
+    width: usize,
+    height: usize,
+    neighborhood: Neighborhood,
+}
+
+impl Triangle {
+    pub fn new(width: usize, height: usize, neighborhood: Neighborhood) -> Triangle {
+        Triangle { width, height, neighborhood }
+    }
+    /// Whether the triangle at (x, y) points up
+    pub fn up(x: usize, y: usize) -> bool {
+        (x + y) & 1 == 0
+    }
+}
+
+impl Topology for Triangle {
+    fn size(&self) -> (usize, usize) {
+        (self.height, self.width)
+    }
+    fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.height && y < self.width
+    }
+    fn neighbors(&self, x: usize, y: usize) -> Vec<Cell> {
+        // The row under an upward triangle is on its flat side
+        let flat: isize = if Triangle::up(x, y) { 1 } else { -1 };
+        let steps: Vec<(isize, isize)> = match self.neighborhood {
+            Neighborhood::Edge => vec![(0, 1), (0, -1), (flat, 0)],
+            Neighborhood::Vertex => {
+                let mut steps: Vec<(isize, isize)> = vec![(0, 1), (0, -1), (0, 2), (0, -2)];
+                steps.extend((-2..=2).map(|dy| (flat, dy)));
+                steps.extend((-1..=1).map(|dy| (-flat, dy)));
+                steps
+            },
+        };
+        steps
+            .iter()
+            .filter_map(|&(dx, dy)| {
+                let nx = x.checked_add_signed(dx)?;
+                let ny = y.checked_add_signed(dy)?;
+                self.contains(nx, ny).then_some((nx, ny))
+            })
+            .collect()
+    }
+}
+
+/// Cubes in `depth` layers of `height` rows, up to 26 neighbors: 8 on
+/// the same layer and 9 on each layer around it. The layers are stored one
+/// under the other, row x is row `x % height` of layer `x / height`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Cube {
+    width: usize,
+    height: usize,
+    depth: usize,
+}
+
+impl Cube {
+    pub fn new(width: usize, height: usize, depth: usize) -> Cube {
+        Cube { width, height, depth }
+    }
+}
+
+impl Topology for Cube {
+    fn size(&self) -> (usize, usize) {
+        (self.height * self.depth, self.width)
+    }
+    fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.height * self.depth && y < self.width
+    }
+    fn neighbors(&self, x: usize, y: usize) -> Vec<Cell> {
+        let (layer, row) = (x / self.height, x % self.height);
+        let mut res = vec![];
+        for dz in -1..=1 {
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    if (dz, dx, dy) == (0, 0, 0) {
+                        continue;
+                    }
+                    let (Some(nz), Some(nx), Some(ny)) = (
+                        layer.checked_add_signed(dz),
+                        row.checked_add_signed(dx),
+                        y.checked_add_signed(dy),
+                    ) else {
+                        continue;
+                    };
+                    if nz < self.depth && nx < self.height && ny < self.width {
+                        res.push((nz * self.height + nx, ny));
+                    }
+                }
+            }
+        }
+        res
+    }
+}
+
+/// The rows of a grid seen from `origin`: the plaque at `origin` comes
+/// first and the rows and columns before it wrap around to the end
+pub fn shift<T: Clone>(grid: &[Vec<T>], origin: Cell) -> Vec<Vec<T>> {
+    let height = grid.len();
+    (0..height)
+        .map(|i| {
+            let row = &grid[(i + origin.0) % height];
+            (0..row.len())
+                .map(|j| row[(j + origin.1) % row.len()].clone())
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rectangle_test() {
+        let r = Rectangle::new(3, 2);
+        assert_eq!(r.size(), (2, 3));
+        assert_eq!(r.cells(), vec![(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2)]);
+        assert_eq!(r.degree(), 5);
+        assert_eq!(Rectangle::new(1, 1).degree(), 0);
+        assert_eq!(Rectangle::new(0, 0).cells(), vec![]);
+    }
+    #[test]
+    fn torus_test() {
+        let t = Torus::new(4, 3);
+        assert_eq!(t.neighbors(0, 0), vec![
+            (0, 1), (0, 3), (1, 0), (2, 0),
+            (1, 1), (1, 3), (2, 1), (2, 3)
+        ]);
+        assert!(t.cells().iter().all(|&(x, y)| t.neighbors(x, y).len() == 8));
+        // Left and right are the same plaque on a board 2 wide
+        assert_eq!(Torus::new(2, 3).neighbors(0, 0).len(), 5);
+        assert_eq!(Torus::new(1, 1).neighbors(0, 0), vec![]);
+    }
+    #[test]
+    fn hex_test() {
+        let h = Hex::new(3, 3);
+        assert_eq!(h.neighbors(1, 1), vec![(1, 2), (1, 0), (2, 2), (2, 1), (0, 2), (0, 1)]);
+        assert_eq!(h.neighbors(0, 0), vec![(0, 1), (1, 0)]);
+        assert_eq!(h.degree(), 6);
+        for (x, y) in h.cells() {
+            let (q, r) = Hex::to_axial(x, y);
+            assert_eq!(Hex::from_axial(q, r), Some((x, y)));
+        }
+        assert_eq!(Hex::to_axial(3, 1), (0, 3));
+        assert_eq!(Hex::from_axial(-2, 2), None);
+    }
+    #[test]
+    fn triangle_test() {
+        let edge = Triangle::new(5, 3, Neighborhood::Edge);
+        assert_eq!(edge.neighbors(1, 2), vec![(1, 3), (1, 1), (0, 2)]);
+        assert_eq!(edge.neighbors(0, 0), vec![(0, 1), (1, 0)]);
+        let vertex = Triangle::new(5, 3, Neighborhood::Vertex);
+        assert_eq!(vertex.degree(), 12);
+        // (1, 2) points down, its flat side is on the row above
+        assert_eq!(vertex.neighbors(1, 2), vec![
+            (1, 3), (1, 1), (1, 4), (1, 0),
+            (0, 0), (0, 1), (0, 2), (0, 3), (0, 4),
+            (2, 1), (2, 2), (2, 3)
+        ]);
+        for (x, y) in vertex.cells() {
+            for (nx, ny) in vertex.neighbors(x, y) {
+                assert!(vertex.neighbors(nx, ny).contains(&(x, y)));
+            }
+        }
+    }
+    #[test]
+    fn cube_test() {
+        let c = Cube::new(3, 3, 3);
+        assert_eq!(c.size(), (9, 3));
+        assert_eq!(c.degree(), 26);
+        // A corner of the first layer, then the same corner one layer down
+        assert_eq!(c.neighbors(0, 0), vec![
+            (0, 1), (1, 0), (1, 1),
+            (3, 0), (3, 1), (4, 0), (4, 1)
+        ]);
+        // The middle of an edge of the middle layer
+        assert_eq!(c.neighbors(3, 1).len(), 17);
+    }
+    #[test]
+    fn shift_test() {
+        let grid = vec![vec![1, 2, 3], vec![4, 5, 6]];
+        assert_eq!(shift(&grid, (0, 0)), grid);
+        assert_eq!(shift(&grid, (1, 2)), vec![vec![6, 4, 5], vec![3, 1, 2]]);
+    }
+}
@@ -0,0 +1,40 @@
+//! JSON files with a format version
+//!
+//! Saved games, replays and statistics are JSON objects with a `version`
+//! field, bumped on every incompatible change of their format. The version
+//! is read first, the rest of a file written by another version may not parse.
+
+use crate::MinesweeperError;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::path::Path;
+
+/// Read a file written by `write`, it must be of version `expected`
+pub(crate) fn read<T>(path: &Path, expected: u32) -> Result<T, MinesweeperError>
+where T: DeserializeOwned {
+    parse(&std::fs::read_to_string(path)?, expected)
+}
+
+/// Read the text of a file written by `write`, see `read`
+pub(crate) fn parse<T>(text: &str, expected: u32) -> Result<T, MinesweeperError>
+where T: DeserializeOwned {
+    #[derive(Deserialize)]
+    struct Header {
+        version: u32,
+    }
+    let header: Header = serde_json::from_str(text).map_err(invalid)?;
+    if header.version != expected {
+        return Err(MinesweeperError::SaveVersion { found: header.version, expected });
+    }
+    serde_json::from_str(text).map_err(invalid)
+}
+
+/// Write `value`, whose `version` field is already set
+pub(crate) fn write<T>(path: &Path, value: &T) -> Result<(), MinesweeperError>
+where T: Serialize {
+    let text = serde_json::to_string(value).map_err(invalid)?;
+    Ok(std::fs::write(path, text)?)
+}
+
+fn invalid(e: serde_json::Error) -> MinesweeperError {
+    MinesweeperError::SaveFormat { message: e.to_string() }
+}
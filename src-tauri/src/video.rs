@@ -0,0 +1,606 @@
+//! Videos of other minesweeper programs
+//!
+//! Minesweeper Arbiter (`.avf`), Vienna Minesweeper (`.rmv`) and
+//! Minesweeper Clone 0.97 (`.mvf`) record the layout and every mouse event
+//! of a game. The events are turned back into `Input`s and played on the
+//! layout, the game must end the way the file says, in the same time.
+//!
+//! All numbers are big-endian, positions are pixels from the top left
+//! plaque, plaques are 16 pixels wide. The layouts read here are:
+//!
+//! ```text
+//! avf  version (1), skipped (4), mode (1): 3 beginner, 4 intermediate,
+//!      5 expert, 6 custom followed by width - 1 (1), height - 1 (1), mines (2)
+//!      mines: row + 1 (1), column + 1 (1)
+//!      text up to the first ']'
+//!      events of 8 bytes: type, x high, seconds low, x low, hundredths,
+//!      y high, seconds high, y low; type 1 move, 3 left down, 5 left up,
+//!      9 right down, 17 right up, 33 middle down, 65 middle up, 0 ends
+//!      text, the time is given as `Time=<seconds>`
+//! rmv  "*rmv", type = 1 (2), sizes of the result string, the version,
+//!      the player, the board, the flags and the properties (2 each),
+//!      of the events (4) and of the checksum (2), then each part in order
+//!      board: width (1), height (1), mines (2), mines: column (1), row (1)
+//!      flags: count (2), flags: column (1), row (1)
+//!      events: type (1), 0 followed by 4 skipped bytes, 1 move, 2 left down,
+//!      3 left up, 4 right down, 5 right up, 6 middle down, 7 middle up
+//!      followed by milliseconds (3), x + 12 (2), y + 56 (2), 9 to 14 and
+//!      18 to 27 followed by 2 skipped bytes, 15 lost, 16 won, 17 ends
+//! mvf  0x11 0x4d, skipped up to byte 74, mode (1), width (1), height (1),
+//!      mines (2), seconds (2), hundredths (1), mines: column + 1 (1),
+//!      row + 1 (1), events count (3), events of 8 bytes: milliseconds (3),
+//!      buttons held (1): 1 left, 2 right, 4 middle, x (2), y (2)
+//! ```
+
+use crate::{
+    replay::{Event, Replay, Step},
+    Action,
+    Game,
+    GameStatus,
+    Input,
+    MinesweeperError,
+};
+use std::path::Path;
+
+/// Pixels of a plaque side
+const PLAQUE: usize = 16;
+/// Milliseconds the rebuilt time may be off, the files count hundredths
+const TIME_TOLERANCE: u64 = 10;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Format {
+    Avf,
+    Rmv,
+    Mvf,
+}
+
+impl Format {
+    /// The format of a file from its extension
+    pub fn from_path(path: &Path) -> Option<Format> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "avf" => Some(Format::Avf),
+            "rmv" => Some(Format::Rmv),
+            "mvf" => Some(Format::Mvf),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Mouse {
+    Move,
+    LeftDown,
+    LeftUp,
+    RightDown,
+    RightUp,
+    MiddleDown,
+    MiddleUp,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct MouseEvent {
+    /// Milliseconds
+    time: u64,
+    mouse: Mouse,
+    x: usize,
+    y: usize,
+}
+
+/// What a video file tells about a game
+#[derive(Debug)]
+pub struct Video {
+    width: usize,
+    height: usize,
+    /// Mines as (row, column)
+    mines: Vec<(usize, usize)>,
+    /// Flags placed before the game started, as (row, column)
+    flags: Vec<(usize, usize)>,
+    events: Vec<MouseEvent>,
+    /// How the game ended, if the file tells
+    status: Option<GameStatus>,
+    /// Milliseconds from the first click to the end, if the file tells
+    time: Option<u64>,
+}
+
+fn invalid(message: &str) -> MinesweeperError {
+    MinesweeperError::Video { message: message.to_string() }
+}
+
+/// Big-endian reads that fail at the end of the data
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Reader<'a> {
+        Reader { data, pos: 0 }
+    }
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], MinesweeperError> {
+        let bytes = self.data
+            .get(self.pos..self.pos + n)
+            .ok_or_else(|| invalid("unexpected end of file"))?;
+        self.pos += n;
+        Ok(bytes)
+    }
+    fn uint(&mut self, n: usize) -> Result<usize, MinesweeperError> {
+        Ok(self.bytes(n)?.iter().fold(0, |acc, &b| acc << 8 | b as usize))
+    }
+    fn u8(&mut self) -> Result<usize, MinesweeperError> {
+        self.uint(1)
+    }
+    fn u16(&mut self) -> Result<usize, MinesweeperError> {
+        self.uint(2)
+    }
+    fn done(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+}
+
+/// Size of the standard levels, modes count from `first`
+fn level(mode: usize, first: usize) -> Option<(usize, usize, usize)> {
+    match mode.checked_sub(first)? {
+        0 => Some((8, 8, 10)),
+        1 => Some((16, 16, 40)),
+        2 => Some((30, 16, 99)),
+        _ => None,
+    }
+}
+
+impl Video {
+    /// Read a video file, the format comes from the extension
+    pub fn read(path: &Path) -> Result<Video, MinesweeperError> {
+        let format = Format::from_path(path)
+            .ok_or_else(|| invalid("unknown extension, expected avf, rmv or mvf"))?;
+        let data = std::fs::read(path)?;
+        Video::parse(format, &data)
+    }
+    pub fn parse(format: Format, data: &[u8]) -> Result<Video, MinesweeperError> {
+        let video = match format {
+            Format::Avf => Video::avf(data)?,
+            Format::Rmv => Video::rmv(data)?,
+            Format::Mvf => Video::mvf(data)?,
+        };
+        let on_board = |&(x, y): &(usize, usize)| x < video.height && y < video.width;
+        if !video.mines.iter().all(on_board) || !video.flags.iter().all(on_board) {
+            return Err(invalid("a mine or a flag is out of the board"));
+        }
+        Ok(video)
+    }
+    fn avf(data: &[u8]) -> Result<Video, MinesweeperError> {
+        let mut r = Reader::new(data);
+        r.bytes(5)?;
+        let mode = r.u8()?;
+        let (width, height, count) = match level(mode, 3) {
+            Some(size) => size,
+            None if mode == 6 => (r.u8()? + 1, r.u8()? + 1, r.u16()?),
+            None => return Err(invalid("unknown mode")),
+        };
+        let mut mines = vec![];
+        for _ in 0..count {
+            let (x, y) = (r.u8()?, r.u8()?);
+            if x == 0 || y == 0 {
+                return Err(invalid("a mine is out of the board"));
+            }
+            mines.push((x - 1, y - 1));
+        }
+        while r.bytes(1)? != b"]" {}
+        let mut events = vec![];
+        loop {
+            let c = r.bytes(8)?;
+            let mouse = match c[0] {
+                0 => break,
+                1 => Mouse::Move,
+                3 => Mouse::LeftDown,
+                5 => Mouse::LeftUp,
+                9 => Mouse::RightDown,
+                17 => Mouse::RightUp,
+                33 => Mouse::MiddleDown,
+                65 => Mouse::MiddleUp,
+                _ => continue,
+            };
+            let seconds = (c[6] as u64) << 8 | c[2] as u64;
+            events.push(MouseEvent {
+                time: seconds * 1000 + c[4] as u64 * 10,
+                mouse,
+                x: (c[1] as usize) << 8 | c[3] as usize,
+                y: (c[5] as usize) << 8 | c[7] as usize,
+            });
+        }
+        let footer = String::from_utf8_lossy(&data[r.pos..]);
+        let time = footer
+            .split("Time=")
+            .nth(1)
+            .and_then(|t| {
+                let end = t.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(t.len());
+                t[..end].parse::<f64>().ok()
+            })
+            .map(|seconds| (seconds * 1000.0).round() as u64);
+        Ok(Video { width, height, mines, flags: vec![], events, status: None, time })
+    }
+    fn rmv(data: &[u8]) -> Result<Video, MinesweeperError> {
+        let mut r = Reader::new(data);
+        if r.bytes(4)? != b"*rmv" || r.u16()? != 1 {
+            return Err(invalid("not a Vienna Minesweeper file"));
+        }
+        let mut sizes = vec![];
+        for _ in 0..6 {
+            sizes.push(r.u16()?);
+        }
+        let video_size = r.uint(4)?;
+        r.u16()?;
+        // The result string, the version and the player are not needed
+        r.bytes(sizes[0] + sizes[1] + sizes[2])?;
+        let mut board = Reader::new(r.bytes(sizes[3])?);
+        let (width, height, count) = (board.u8()?, board.u8()?, board.u16()?);
+        let mut mines = vec![];
+        for _ in 0..count {
+            let (y, x) = (board.u8()?, board.u8()?);
+            mines.push((x, y));
+        }
+        let mut flags = vec![];
+        let mut preflags = Reader::new(r.bytes(sizes[4])?);
+        if !preflags.done() {
+            for _ in 0..preflags.u16()? {
+                let (y, x) = (preflags.u8()?, preflags.u8()?);
+                flags.push((x, y));
+            }
+        }
+        r.bytes(sizes[5])?;
+        let mut v = Reader::new(r.bytes(video_size)?);
+        let mut events = vec![];
+        let mut status = None;
+        while !v.done() {
+            let c = v.u8()?;
+            let mouse = match c {
+                0 => {
+                    v.bytes(4)?;
+                    continue;
+                },
+                1 => Mouse::Move,
+                2 => Mouse::LeftDown,
+                3 => Mouse::LeftUp,
+                4 => Mouse::RightDown,
+                5 => Mouse::RightUp,
+                6 => Mouse::MiddleDown,
+                7 => Mouse::MiddleUp,
+                9..=14 | 18..=27 => {
+                    v.bytes(2)?;
+                    continue;
+                },
+                15..=17 => {
+                    status = match c {
+                        15 => Some(GameStatus::Lost),
+                        16 => Some(GameStatus::Won),
+                        _ => None,
+                    };
+                    break;
+                },
+                _ => return Err(invalid("unknown event")),
+            };
+            let time = v.uint(3)? as u64;
+            // Positions left or above the board wrap out of it
+            let x = v.u16()?.wrapping_sub(12);
+            let y = v.u16()?.wrapping_sub(56);
+            events.push(MouseEvent { time, mouse, x, y });
+        }
+        Ok(Video { width, height, mines, flags, events, status, time: None })
+    }
+    fn mvf(data: &[u8]) -> Result<Video, MinesweeperError> {
+        let mut r = Reader::new(data);
+        if r.bytes(2)? != [0x11, 0x4d] {
+            return Err(invalid("only Minesweeper Clone 0.97 files can be read"));
+        }
+        r.bytes(72)?;
+        let mode = r.u8()?;
+        let (w, h, m) = (r.u8()?, r.u8()?, r.u16()?);
+        let (width, height, count) = level(mode, 1).unwrap_or((w, h, m));
+        let time = r.u16()? as u64 * 1000 + r.u8()? as u64 * 10;
+        let mut mines = vec![];
+        for _ in 0..count {
+            let (y, x) = (r.u8()?, r.u8()?);
+            if x == 0 || y == 0 {
+                return Err(invalid("a mine is out of the board"));
+            }
+            mines.push((x - 1, y - 1));
+        }
+        let mut events = vec![];
+        let mut held = 0;
+        for _ in 0..r.uint(3)? {
+            let time = r.uint(3)? as u64;
+            let buttons = r.u8()?;
+            let (x, y) = (r.u16()?, r.u16()?);
+            let changes = [
+                (1, Mouse::LeftDown, Mouse::LeftUp),
+                (2, Mouse::RightDown, Mouse::RightUp),
+                (4, Mouse::MiddleDown, Mouse::MiddleUp),
+            ];
+            let mut moved = true;
+            for (bit, down, up) in changes {
+                if (buttons ^ held) & bit != 0 {
+                    let mouse = if buttons & bit != 0 { down } else { up };
+                    events.push(MouseEvent { time, mouse, x, y });
+                    moved = false;
+                }
+            }
+            if moved {
+                events.push(MouseEvent { time, mouse: Mouse::Move, x, y });
+            }
+            held = buttons;
+        }
+        Ok(Video { width, height, mines, flags: vec![], events, status: None, time: Some(time) })
+    }
+    /// The layout in the text format of `layout`
+    fn layout(&self) -> String {
+        let mut rows = vec![vec!['.'; self.width]; self.height];
+        for &(x, y) in &self.mines {
+            rows[x][y] = '*';
+        }
+        let mut res = format!("{} {} {}\n", self.width, self.height, self.mines.len());
+        for row in rows {
+            res.extend(row);
+            res.push('\n');
+        }
+        res
+    }
+    fn plaque(&self, event: &MouseEvent) -> Option<(usize, usize)> {
+        let (x, y) = (event.y / PLAQUE, event.x / PLAQUE);
+        (x < self.height && y < self.width).then_some((x, y))
+    }
+    /// The moves of the mouse events, a release of both buttons or of the
+    /// middle one chords, a left release clicks, a right press flags
+    fn steps(&self) -> Vec<Step> {
+        let start = self.events.first().map_or(0, |e| e.time);
+        let mut steps: Vec<Step> = self.flags
+            .iter()
+            .map(|&(x, y)| Step {
+                time: 0,
+                event: Event::Input(Input { x, y, act: Action::RightClick }),
+            })
+            .collect();
+        let (mut left, mut right, mut chorded) = (false, false, false);
+        for event in &self.events {
+            let act = match event.mouse {
+                Mouse::Move | Mouse::MiddleDown => None,
+                Mouse::LeftDown => {
+                    left = true;
+                    None
+                },
+                Mouse::RightDown => {
+                    right = true;
+                    (!left).then_some(Action::RightClick)
+                },
+                Mouse::LeftUp | Mouse::RightUp => {
+                    let both = left && right;
+                    let act = if both {
+                        chorded = true;
+                        Some(Action::Chord)
+                    } else if event.mouse == Mouse::LeftUp && left && !chorded {
+                        Some(Action::Click)
+                    } else {
+                        None
+                    };
+                    if event.mouse == Mouse::LeftUp { left = false } else { right = false }
+                    if !left && !right {
+                        chorded = false;
+                    }
+                    act
+                },
+                Mouse::MiddleUp => Some(Action::Chord),
+            };
+            if let (Some(act), Some((x, y))) = (act, self.plaque(event)) {
+                steps.push(Step {
+                    time: event.time.saturating_sub(start),
+                    event: Event::Input(Input { x, y, act }),
+                });
+            }
+        }
+        steps
+    }
+    /// Play the video on its layout and check that it ends as recorded.
+    /// The time runs from the first click to the move ending the game
+    pub fn replay(&self) -> Result<Replay, MinesweeperError> {
+        let layout = self.layout();
+        let mut steps = self.steps();
+        let mut game = Game::from_layout(&layout)?;
+        let mut first_click = None;
+        let mut end = None;
+        for (i, step) in steps.iter().enumerate() {
+            if let Event::Input(input) = &step.event {
+                if input.act == Action::Click {
+                    first_click.get_or_insert(step.time);
+                }
+                let _ = game.try_update(input);
+                if game.status != GameStatus::InProgress {
+                    end = Some((i, step.time));
+                    break;
+                }
+            }
+        }
+        // What the player did after the end is not part of the game
+        if let Some((i, _)) = end {
+            steps.truncate(i + 1);
+        }
+        let end = end.map(|(_, time)| time);
+        if let Some(expected) = self.status {
+            if game.status != expected {
+                return Err(MinesweeperError::ReplayMismatch { expected, found: game.status });
+            }
+        }
+        if let Some(expected) = self.time {
+            // A game still in progress is timed up to the last step,
+            // the file says it ended so it is rejected whatever the time
+            let last = end.or(steps.last().map(|s| s.time)).unwrap_or(0);
+            let found = last
+                .checked_sub(first_click.unwrap_or(last))
+                .ok_or_else(|| invalid("the game ends before the first click"))?;
+            if end.is_none() || found.abs_diff(expected) > TIME_TOLERANCE {
+                return Err(MinesweeperError::ReplayTime { expected, found });
+            }
+        }
+        Ok(Replay::imported(game.config, layout, steps, game.status))
+    }
+}
+
+/// Read a video file and check it, see `Video::replay`
+pub fn import(path: &Path) -> Result<Replay, MinesweeperError> {
+    Video::read(path)?.replay()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Beginner board with mines on the first row except in the corner
+    fn avf(events: &[(u8, usize, usize, u64)], time: &str) -> Vec<u8> {
+        let mut data = vec![0, 0, 0, 0, 0, 3];
+        for y in 2..=8 {
+            data.extend([1, y]);
+        }
+        for (x, y) in [(2, 1), (2, 2), (2, 3)] {
+            data.extend([x, y]);
+        }
+        data.extend(b"[0|beginner]");
+        for &(t, x, y, ms) in events {
+            let (s, h) = (ms / 1000, ms % 1000 / 10);
+            data.extend([
+                t, (x >> 8) as u8, s as u8, x as u8,
+                h as u8, (y >> 8) as u8, (s >> 8) as u8, y as u8,
+            ]);
+        }
+        data.extend([0; 8]);
+        data.extend(time.as_bytes());
+        data
+    }
+
+    #[test]
+    fn video_avf_test() {
+        let events = [(1, 60, 100, 0), (3, 60, 100, 500), (5, 60, 100, 620)];
+        let video = Video::parse(Format::Avf, &avf(&events, "")).unwrap();
+        assert_eq!((video.width, video.height, video.mines.len()), (8, 8, 10));
+        assert!(video.mines.contains(&(0, 1)) && video.mines.contains(&(1, 2)));
+        assert_eq!(video.events.len(), 3);
+        assert_eq!(video.events[2], MouseEvent { time: 620, mouse: Mouse::LeftUp, x: 60, y: 100 });
+        let replay = video.replay().unwrap();
+        assert_eq!(replay.status, GameStatus::InProgress);
+        assert_eq!(
+            replay.steps,
+            vec![Step { time: 620, event: Event::Input(Input { x: 6, y: 3, act: Action::Click }) }]
+        );
+        // A time is only written once the game is over
+        assert_eq!(
+            Video::parse(Format::Avf, &avf(&events, "Time=1.20")).unwrap().replay().err(),
+            Some(MinesweeperError::ReplayTime { expected: 1200, found: 0 })
+        );
+    }
+    #[test]
+    fn video_avf_error_test() {
+        // The corner is safe but its neighbours are mines,
+        // clicking (1, 1) loses
+        let lost = avf(&[(3, 20, 20, 0), (5, 20, 20, 10)], "");
+        let mut video = Video::parse(Format::Avf, &lost).unwrap();
+        assert_eq!(video.replay().unwrap().status, GameStatus::Lost);
+        // A right click after the loss is dropped
+        let after = avf(&[(3, 20, 20, 0), (5, 20, 20, 10), (9, 60, 100, 20), (17, 60, 100, 30)], "");
+        let replay = Video::parse(Format::Avf, &after).unwrap().replay().unwrap();
+        assert_eq!((replay.steps.len(), replay.status), (1, GameStatus::Lost));
+        video.status = Some(GameStatus::Won);
+        assert_eq!(
+            video.replay().err(),
+            Some(MinesweeperError::ReplayMismatch {
+                expected: GameStatus::Won,
+                found: GameStatus::Lost,
+            })
+        );
+        let slow = avf(&[(3, 20, 20, 0), (5, 20, 20, 10)], "Time=3.50");
+        assert_eq!(
+            Video::parse(Format::Avf, &slow).unwrap().replay().err(),
+            Some(MinesweeperError::ReplayTime { expected: 3500, found: 0 })
+        );
+        assert!(Video::parse(Format::Avf, &lost[..20]).is_err());
+        // A move at 0 s, a safe click on (6, 3) at 0.5 s, then the loss stamped 0.1 s
+        let backwards = avf(
+            &[
+                (1, 60, 100, 0),
+                (3, 60, 100, 500), (5, 60, 100, 500),
+                (3, 20, 20, 100), (5, 20, 20, 100),
+            ],
+            "Time=0.40",
+        );
+        assert_eq!(
+            Video::parse(Format::Avf, &backwards).unwrap().replay().err(),
+            Some(invalid("the game ends before the first click"))
+        );
+    }
+    #[test]
+    fn video_rmv_test() {
+        let board = [3, 1, 0, 1, 0, 0];
+        let events: Vec<u8> = vec![
+            0, 0, 0, 0, 0,
+            // right click on the mine at (0, 0)
+            4, 0, 0, 100, 0, 12 + 8, 0, 56 + 8,
+            5, 0, 0, 150, 0, 12 + 8, 0, 56 + 8,
+            // left click on (0, 2)
+            2, 0, 1, 0, 0, 12 + 40, 0, 56 + 8,
+            3, 0, 1, 50, 0, 12 + 40, 0, 56 + 8,
+            16,
+        ];
+        let mut data = b"*rmv".to_vec();
+        data.extend([0, 1]);
+        for size in [1, 0, 0, board.len(), 0, 0] {
+            data.extend([0, size as u8]);
+        }
+        data.extend([0, 0, 0, events.len() as u8, 0, 0]);
+        data.push(b'x');
+        data.extend(board);
+        data.extend(&events);
+        let video = Video::parse(Format::Rmv, &data).unwrap();
+        assert_eq!(video.mines, vec![(0, 0)]);
+        assert_eq!(video.status, Some(GameStatus::Won));
+        let replay = video.replay().unwrap();
+        assert_eq!(replay.status, GameStatus::Won);
+        assert_eq!(replay.steps.len(), 2);
+        assert_eq!(replay.steps[1].time, 206);
+        assert!(Video::parse(Format::Rmv, b"*rmx").is_err());
+    }
+    #[test]
+    fn video_mvf_test() {
+        let mut data = vec![0x11, 0x4d];
+        data.extend([0; 72]);
+        data.extend([4, 2, 2, 0, 1, 0, 0, 0, 2, 2]);
+        // left down and up on (0, 0), then a left click on (0, 1)
+        data.extend([0, 0, 3]);
+        data.extend([0, 0, 0, 1, 0, 4, 0, 4]);
+        data.extend([0, 0, 9, 0, 0, 4, 0, 4]);
+        data.extend([0, 0, 9, 1, 0, 20, 0, 4]);
+        let video = Video::parse(Format::Mvf, &data).unwrap();
+        assert_eq!((video.width, video.height, video.mines.clone()), (2, 2, vec![(1, 1)]));
+        assert_eq!(video.events.len(), 3);
+        // The file is timed 0.05 s but the game does not end
+        data[81] = 5;
+        assert_eq!(
+            Video::parse(Format::Mvf, &data).unwrap().replay().err(),
+            Some(MinesweeperError::ReplayTime { expected: 50, found: 0 })
+        );
+        // then left clicks on (0, 1) and (1, 0) win after 21 ms
+        data.extend([0, 0, 12, 0, 0, 20, 0, 4]);
+        data.extend([0, 0, 12, 1, 0, 4, 0, 20]);
+        data.extend([0, 0, 30, 0, 0, 4, 0, 20]);
+        data[86] = 6;
+        assert_eq!(
+            Video::parse(Format::Mvf, &data).unwrap().replay().err(),
+            Some(MinesweeperError::ReplayTime { expected: 50, found: 21 })
+        );
+        data[81] = 2;
+        let replay = Video::parse(Format::Mvf, &data).unwrap().replay().unwrap();
+        assert_eq!(replay.status, GameStatus::Won);
+        assert!(Video::parse(Format::Mvf, &[0, 0, 0]).is_err());
+    }
+    #[test]
+    fn video_format_test() {
+        assert_eq!(Format::from_path(Path::new("a/b.AVF")), Some(Format::Avf));
+        assert_eq!(Format::from_path(Path::new("b.rmv")), Some(Format::Rmv));
+        assert_eq!(Format::from_path(Path::new("b.txt")), None);
+    }
+}
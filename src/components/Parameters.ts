@@ -17,7 +17,7 @@ export class Parameter {
     }
 
     async listen() {
-        await listen<string>('parameter-init', (event) => {
+        await listen<string>('parameter_init', (event) => {
             console.log(event.payload)
             this.init(event.payload)
         })